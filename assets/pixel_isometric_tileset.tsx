@@ -2,4 +2,381 @@
 <tileset version="1.10" tiledversion="1.11.1" name="pixel_isometric_tileset" tilewidth="32" tileheight="32" tilecount="121" columns="11">
  <grid orientation="isometric" width="32" height="32"/>
  <image source="spritesheet.png" width="352" height="352"/>
+ <tile id="44">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="45">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="46">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="47">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="48">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="49">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="50">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="51">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="52">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="53">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="54">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+  </properties>
+ </tile>
+ <tile id="55">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="56">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="57">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="58">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="59">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="66">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="67">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="68">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="69">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="70">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="71">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="72">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="73">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="74">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="75">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="76">
+  <properties>
+   <property name="movement_cost" type="int" value="2"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="77">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="78">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="79">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="80">
+  <properties>
+   <property name="blocks_line_of_sight" type="bool" value="true"/>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="88">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="89">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="90">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="91">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="92">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="93">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="94">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="95">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="96">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="97">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="98">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="99">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="100">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="101">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="102">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="103">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="104">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="105">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="106">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="107">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="108">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="109">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="110">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="111">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="112">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="113">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="114">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="115">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="116">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="117">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="118">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="119">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="120">
+  <properties>
+   <property name="walkable" type="bool" value="false"/>
+   <property name="water" type="bool" value="true"/>
+  </properties>
+ </tile>
 </tileset>
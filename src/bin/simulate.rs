@@ -0,0 +1,406 @@
+//! Plays AI-vs-AI matches without a window and writes statistics about each card.
+//!
+//! ```sh
+//! cargo run --release --bin simulate -- --map assets/map1.tmx --deck1 assets/decks/goblins.ron \
+//!     --deck2 assets/decks/spells.ron --matches 1000 --out stats.csv
+//! ```
+//!
+//! Every card of `assets/cards` is part of the decks (the starter deck when no deck file
+//! is given). The statistics are written in CSV or JSON depending on the extension of
+//! `--out`, in CSV on the standard output by default.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+};
+
+use cards::game::{
+    ai::{Ai, Difficulty},
+    card_data::{load_cards, load_deck, starter_deck},
+    deck::Deck,
+    mcts::SearchBudget,
+    player::{Player, PlayerId},
+    rules::{Action, Card, CreatureId, Event, Match, VictoryCondition},
+    tiled::TiledMap,
+    world::World,
+};
+use serde::Serialize;
+
+const CARDS_DIR: &str = "assets/cards";
+/// Matches still running after this turn are draws
+const MAX_TURNS: u32 = 200;
+
+const USAGE: &str = "Usage: simulate [--map FILE] [--deck1 FILE] [--deck2 FILE] [--matches N]
+                [--ai DIFFICULTY] [--ai1 DIFFICULTY] [--ai2 DIFFICULTY]
+                [--iterations N] [--seed N] [--victory CONDITIONS]
+                [--out FILE.csv|FILE.json]
+
+  --map         map file, TMX or JSON (default: assets/map1.tmx)
+  --deck1/2     deck files, a list of (card name, copies) (default: the starter deck)
+  --matches     number of matches (default: 1000)
+  --ai          difficulty of both AIs: random, greedy, lookahead or mcts (default: greedy)
+  --ai1/2       difficulty of the AI playing each deck
+  --iterations  iterations of the MCTS for each action (default: 400)
+  --seed        seed of the first match, the next ones follow (default: 0)
+  --victory     ways to win separated by commas: health, objectives[:TURNS] (3 turns by
+                default), elimination or deckout (default: health)
+  --out         file of the statistics, CSV on the standard output by default";
+
+/// Options given on the command line
+struct Options {
+    map: PathBuf,
+    decks: [Option<PathBuf>; 2],
+    matches: u32,
+    difficulties: [Difficulty; 2],
+    iterations: u32,
+    seed: u64,
+    victory_conditions: Vec<VictoryCondition>,
+    out: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            map: PathBuf::from("assets/map1.tmx"),
+            decks: [None, None],
+            matches: 1000,
+            difficulties: [Difficulty::Greedy; 2],
+            iterations: SearchBudget::default().iterations,
+            seed: 0,
+            victory_conditions: VictoryCondition::DEFAULT.to_vec(),
+            out: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", arg))?;
+            let number = |value: &str| {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("{} needs a number, not \"{}\"", arg, value))
+            };
+            match arg.as_str() {
+                "--map" => options.map = PathBuf::from(value),
+                "--deck1" => options.decks[0] = Some(PathBuf::from(value)),
+                "--deck2" => options.decks[1] = Some(PathBuf::from(value)),
+                "--matches" => options.matches = number(&value)? as u32,
+                "--ai" => options.difficulties = [value.parse()?; 2],
+                "--ai1" => options.difficulties[0] = value.parse()?,
+                "--ai2" => options.difficulties[1] = value.parse()?,
+                "--iterations" => options.iterations = number(&value)? as u32,
+                "--seed" => options.seed = number(&value)?,
+                "--victory" => options.victory_conditions = VictoryCondition::parse_list(&value)?,
+                "--out" => options.out = Some(PathBuf::from(value)),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// What a deck did during one match
+#[derive(Default)]
+struct DeckRecord {
+    /// Number of times each card was played
+    plays: HashMap<String, u32>,
+    /// Damage dealt by each card: its effect, its creature and their tokens
+    damage: HashMap<String, u32>,
+}
+
+/// Result of one match, the decks are indexed as given on the command line
+struct MatchRecord {
+    /// Deck which won, `None` for a draw
+    winner: Option<usize>,
+    turns: u32,
+    decks: [DeckRecord; 2],
+}
+
+/// Statistics of a card over every match
+#[derive(Debug, Default, Serialize)]
+struct CardStats {
+    card: String,
+    /// Matches in which a deck had the card, counted once per deck
+    in_deck: u32,
+    /// Matches in which a deck played the card at least once, counted once per deck
+    played_in: u32,
+    /// `played_in / in_deck`
+    play_rate: f64,
+    /// Number of times the card was played
+    plays: u32,
+    /// Matches won by the decks which played the card, over `played_in`
+    win_rate_when_played: f64,
+    /// Damage dealt by the card for each time it was played
+    average_damage: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    matches: u32,
+    /// Matches won by each deck
+    wins: [u32; 2],
+    draws: u32,
+    average_turns: f64,
+    cards: Vec<CardStats>,
+}
+
+fn main() -> ExitCode {
+    if std::env::args().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let (cards, errors) = load_cards(Path::new(CARDS_DIR));
+    if let Some(error) = errors.first() {
+        return Err(error.to_string());
+    }
+    let map = TiledMap::load(&options.map).map_err(|error| error.to_string())?;
+    let world = World::from_tiled(&map).map_err(|error| error.to_string())?;
+
+    let mut decks = [Vec::new(), Vec::new()];
+    for (deck, file) in decks.iter_mut().zip(&options.decks) {
+        *deck = match file {
+            Some(file) => load_deck(file, &cards).map_err(|error| error.to_string())?,
+            None => starter_deck(&cards),
+        };
+    }
+
+    // The matches are split between the threads, each one is played from its own seed
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()) as u32;
+    let records: Vec<MatchRecord> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                let (world, decks) = (&world, &decks);
+                scope.spawn(move || {
+                    (thread..options.matches)
+                        .step_by(threads as usize)
+                        .map(|index| play(world, decks, options, index))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("a simulation thread panicked"))
+            .collect()
+    });
+
+    let summary = summarize(&records, &decks);
+    eprintln!(
+        "{} matches: deck 1 won {}, deck 2 won {}, {} draws, {:.1} turns on average",
+        summary.matches, summary.wins[0], summary.wins[1], summary.draws, summary.average_turns
+    );
+
+    let output = match options.out.as_ref().and_then(|out| out.extension()) {
+        Some(ext) if ext == "json" => {
+            serde_json::to_string_pretty(&summary).map_err(|error| error.to_string())?
+        }
+        _ => to_csv(&summary.cards),
+    };
+    match &options.out {
+        Some(out) => {
+            fs::write(out, output).map_err(|error| format!("{}: {}", out.display(), error))?
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// Play the match of the given index
+///
+/// The decks switch sides every match so that none of them always plays first.
+fn play(world: &World, decks: &[Vec<Card>; 2], options: &Options, index: u32) -> MatchRecord {
+    let seed = options.seed.wrapping_add(index as u64);
+    // Deck of each player
+    let sides = if index.is_multiple_of(2) {
+        [0, 1]
+    } else {
+        [1, 0]
+    };
+
+    let players = [0, 1].map(|player: PlayerId| {
+        let cards = decks[sides[player]].clone();
+        let mut deck = Deck::new(player as u32, cards, seed.wrapping_mul(2) + player as u64);
+        deck.shuffle();
+        let mut player = Player::new(player as u32, format!("Deck {}", sides[player] + 1));
+        player.deck = deck;
+        player
+    });
+    let mut game = Match::new(world.clone(), players);
+    game.victory_conditions = options.victory_conditions.clone();
+    let mut ais = [0, 1].map(|player| {
+        let mut ai = Ai::new(
+            player,
+            options.difficulties[sides[player]],
+            seed ^ player as u64,
+        );
+        // Without time limit, the matches can be replayed from their seed
+        ai.budget = SearchBudget {
+            iterations: options.iterations,
+            time: None,
+        };
+        ai
+    });
+
+    let mut records = [DeckRecord::default(), DeckRecord::default()];
+    // Owner of each creature and card which brought it on the board
+    let mut creature_cards: HashMap<CreatureId, (PlayerId, String)> = HashMap::new();
+    while !game.is_over() && game.turn.number <= MAX_TURNS {
+        let player = game.turn.active_player;
+        let Some(action) = ais[player].choose(&game) else {
+            break;
+        };
+
+        // What deals the damage of the action
+        let mut source = match &action {
+            Action::PlayCard { card, .. } => {
+                let name = game.players[player].hand[*card]
+                    .get_basic_info()
+                    .name
+                    .clone();
+                *records[sides[player]]
+                    .plays
+                    .entry(name.clone())
+                    .or_default() += 1;
+                Some((player, name))
+            }
+            _ => None,
+        };
+        let Ok(events) = game.apply(action) else {
+            break;
+        };
+
+        for event in events {
+            match &event {
+                // Tokens count as their card
+                Event::CreatureSummoned { id, owner, .. } => {
+                    if let Some((_, name)) = &source {
+                        creature_cards.insert(*id, (*owner, name.clone()));
+                    }
+                }
+                // The damage that follows is dealt by the creature, or its on-death effect
+                Event::CreatureAttacked { attacker: id, .. }
+                | Event::CreatureRetaliated { defender: id, .. }
+                | Event::CreatureDied { id, .. } => {
+                    source = creature_cards.get(id).cloned().or(source);
+                }
+                Event::CreatureDamaged { outcome, .. } | Event::PlayerDamaged { outcome, .. } => {
+                    if let Some((owner, name)) = &source {
+                        *records[sides[*owner]]
+                            .damage
+                            .entry(name.clone())
+                            .or_default() += outcome.dealt;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    MatchRecord {
+        winner: game.result.map(|result| sides[result.winner()]),
+        turns: game.turn.number,
+        decks: records,
+    }
+}
+
+/// Statistics of every match, the cards are sorted by name
+fn summarize(records: &[MatchRecord], decks: &[Vec<Card>; 2]) -> Summary {
+    let mut cards: BTreeMap<String, CardStats> = BTreeMap::new();
+    let mut damage: BTreeMap<String, u32> = BTreeMap::new();
+    let mut wins_when_played: BTreeMap<String, u32> = BTreeMap::new();
+    let mut wins = [0; 2];
+    let mut turns = 0;
+
+    for record in records {
+        turns += record.turns;
+        if let Some(winner) = record.winner {
+            wins[winner] += 1;
+        }
+
+        for (index, deck) in record.decks.iter().enumerate() {
+            let mut names: Vec<&String> = decks[index]
+                .iter()
+                .map(|card| &card.get_basic_info().name)
+                .collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let stats = cards.entry(name.clone()).or_insert_with(|| CardStats {
+                    card: name.clone(),
+                    ..Default::default()
+                });
+                stats.in_deck += 1;
+
+                let plays = deck.plays.get(name).copied().unwrap_or(0);
+                if plays > 0 {
+                    stats.played_in += 1;
+                    stats.plays += plays;
+                    if record.winner == Some(index) {
+                        *wins_when_played.entry(name.clone()).or_default() += 1;
+                    }
+                }
+                *damage.entry(name.clone()).or_default() +=
+                    deck.damage.get(name).copied().unwrap_or(0);
+            }
+        }
+    }
+
+    let ratio = |a: f64, b: u32| if b == 0 { 0. } else { a / b as f64 };
+    for stats in cards.values_mut() {
+        stats.play_rate = ratio(stats.played_in as f64, stats.in_deck);
+        let wins = wins_when_played.get(&stats.card).copied().unwrap_or(0);
+        stats.win_rate_when_played = ratio(wins as f64, stats.played_in);
+        stats.average_damage = ratio(damage[&stats.card] as f64, stats.plays);
+    }
+
+    Summary {
+        matches: records.len() as u32,
+        draws: records
+            .iter()
+            .filter(|record| record.winner.is_none())
+            .count() as u32,
+        wins,
+        average_turns: ratio(turns as f64, records.len() as u32),
+        cards: cards.into_values().collect(),
+    }
+}
+
+/// One line per card, after a header
+fn to_csv(cards: &[CardStats]) -> String {
+    let mut csv = String::from(
+        "card,in_deck,played_in,play_rate,plays,win_rate_when_played,average_damage\n",
+    );
+    for stats in cards {
+        csv += &format!(
+            "\"{}\",{},{},{:.4},{},{:.4},{:.2}\n",
+            stats.card.replace('"', "\"\""),
+            stats.in_deck,
+            stats.played_in,
+            stats.play_rate,
+            stats.plays,
+            stats.win_rate_when_played,
+            stats.average_damage,
+        );
+    }
+    csv
+}
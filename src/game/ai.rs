@@ -0,0 +1,333 @@
+//! Computer opponent, it plays through the same actions as the user.
+
+use std::{fmt, str::FromStr};
+
+use super::{
+    life::HasLife,
+    mcts::{self, SearchBudget},
+    player::PlayerId,
+    rules::{rng::Rng, Action, Match, VictoryCondition},
+    world::distance,
+};
+
+/// Score of a won match, far above what any state of a running match is worth
+const VICTORY_SCORE: f32 = 10_000.;
+/// Number of the best actions whose follow-ups are explored by the lookahead search
+const LOOKAHEAD_WIDTH: usize = 8;
+
+/// How the AI chooses its actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Any action allowed by the rules
+    Random,
+    /// The action leading to the best state right after it
+    Greedy,
+    /// The action leading to the best state after it and a follow-up action of the
+    /// same turn
+    Lookahead,
+    /// Monte Carlo tree search within the budget of the AI, see [`super::mcts`]
+    Mcts,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Random,
+        Difficulty::Greedy,
+        Difficulty::Lookahead,
+        Difficulty::Mcts,
+    ];
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Random => "Random",
+            Difficulty::Greedy => "Greedy",
+            Difficulty::Lookahead => "Lookahead",
+            Difficulty::Mcts => "MCTS",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parse the name of a difficulty, case insensitive (ex: "greedy")
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown AI difficulty \"{}\"", name))
+    }
+}
+
+/// Computer player of a match
+///
+/// Given the same seed and the same match, it always chooses the same actions.
+#[derive(Debug, Clone)]
+pub struct Ai {
+    pub player: PlayerId,
+    pub difficulty: Difficulty,
+    /// Limits of the search of [`Difficulty::Mcts`]
+    pub budget: SearchBudget,
+    /// Used to choose between actions of equal value and by the searches
+    rng: Rng,
+}
+
+impl Ai {
+    pub fn new(player: PlayerId, difficulty: Difficulty, seed: u64) -> Self {
+        Ai {
+            player,
+            difficulty,
+            budget: SearchBudget::default(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Next action of the AI, `None` when it is not its turn or the match is over
+    ///
+    /// The action is always accepted by [`Match::apply`]. The AI passes with
+    /// [`Action::NextPhase`] when nothing improves its position, which ends its turn after
+    /// the combat phase.
+    pub fn choose(&mut self, game: &Match) -> Option<Action> {
+        if game.turn.active_player != self.player {
+            return None;
+        }
+        let actions = game.legal_actions();
+        if actions.is_empty() {
+            return None;
+        }
+
+        match self.difficulty {
+            Difficulty::Random => Some(actions[self.rng.below(actions.len())].clone()),
+            Difficulty::Greedy => Some(self.best(game, actions, |ai, after| ai.evaluate(&after))),
+            Difficulty::Lookahead => {
+                // Only the most promising actions are explored further
+                let mut scored: Vec<(Action, f32)> = self
+                    .playable(actions)
+                    .into_iter()
+                    .filter_map(|action| Some((action.clone(), self.score_after(game, &action)?)))
+                    .collect();
+                scored.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+                scored.truncate(LOOKAHEAD_WIDTH);
+
+                let candidates = scored.into_iter().map(|(action, _)| action).collect();
+                Some(self.best(game, candidates, |ai, after| {
+                    let follow_ups = ai.playable(after.legal_actions());
+                    let best_follow_up = follow_ups
+                        .iter()
+                        .filter_map(|action| ai.score_after(&after, action))
+                        .fold(f32::MIN, f32::max);
+                    // Passing keeps the state as it is
+                    best_follow_up.max(ai.evaluate(&after))
+                }))
+            }
+            Difficulty::Mcts => Some(mcts::search(game, self.budget, &mut self.rng)),
+        }
+    }
+
+    /// Action whose resulting state has the best value, or passing if none is better
+    /// than the current state
+    ///
+    /// * `value` : value of the state reached after an action
+    fn best(
+        &mut self,
+        game: &Match,
+        actions: Vec<Action>,
+        value: impl Fn(&Ai, Match) -> f32,
+    ) -> Action {
+        let mut best = (vec![Action::NextPhase], self.evaluate(game));
+        for action in self.playable(actions) {
+            let mut after = game.clone();
+            if after.apply(action.clone()).is_err() {
+                continue;
+            }
+            let score = value(self, after);
+            if score > best.1 {
+                best = (vec![action], score);
+            } else if score == best.1 {
+                best.0.push(action);
+            }
+        }
+
+        let (mut actions, _) = best;
+        let index = self.rng.below(actions.len());
+        actions.swap_remove(index)
+    }
+
+    /// Actions worth simulating: the phase changes are only chosen when passing, the
+    /// AI would see the cards drawn by the opponent otherwise
+    fn playable(&self, actions: Vec<Action>) -> Vec<Action> {
+        actions
+            .into_iter()
+            .filter(|action| !matches!(action, Action::NextPhase | Action::EndTurn))
+            .collect()
+    }
+
+    /// Value of the state after the action, `None` if the rules refuse it
+    fn score_after(&self, game: &Match, action: &Action) -> Option<f32> {
+        let mut after = game.clone();
+        after.apply(action.clone()).ok()?;
+        Some(self.evaluate(&after))
+    }
+
+    /// How good the state is for the AI, see [`evaluate`]
+    pub fn evaluate(&self, game: &Match) -> f32 {
+        evaluate(game, self.player)
+    }
+}
+
+/// How good the state is for `player`, the higher the better
+///
+/// Compares the health of the players, the strength of their creatures, how close
+/// the creatures are to the enemies and the objectives and how long the objectives
+/// have been held.
+pub fn evaluate(game: &Match, player: PlayerId) -> f32 {
+    let opponent = Match::opponent(player);
+    if let Some(result) = game.result {
+        return if result.winner() == player {
+            VICTORY_SCORE
+        } else {
+            -VICTORY_SCORE
+        };
+    }
+    let side = |owner: PlayerId| if owner == player { 1. } else { -1. };
+    let holds_objectives = game
+        .victory_conditions
+        .iter()
+        .any(|condition| matches!(condition, VictoryCondition::HoldObjectives { .. }));
+    let objectives: &[_] = if holds_objectives {
+        &game.world.objectives
+    } else {
+        &[]
+    };
+
+    let mut score = game.players[player].get_life_current() as f32
+        - game.players[opponent].get_life_current() as f32;
+
+    for creature in &game.creatures {
+        let strength = creature.get_life_current() as f32 + 2. * creature.card.attack as f32;
+        // Creatures going toward the enemies and the objectives that win the match, so
+        // that they can fight
+        let closest = game
+            .creatures
+            .iter()
+            .filter(|other| other.owner != creature.owner)
+            .map(|other| other.pos)
+            .chain(objectives.iter().copied())
+            .map(|pos| distance(creature.pos, pos))
+            .min()
+            .unwrap_or(0);
+        // and toward the opponent, the matches would never end otherwise
+        let to_opponent = game
+            .closest_player_tile(Match::opponent(creature.owner), creature.pos)
+            .map_or(0, |pos| distance(creature.pos, pos));
+        score +=
+            side(creature.owner) * (strength - 0.1 * closest as f32 - 0.3 * to_opponent as f32);
+    }
+
+    if holds_objectives {
+        for owner in [player, opponent] {
+            if game.controls_objectives(owner) {
+                score += side(owner) * (10. + 20. * game.stats.objectives_held[owner] as f32);
+            }
+        }
+    }
+
+    // Cards in hand are future creatures and effects
+    score += 2. * game.players[player].hand.len() as f32;
+    score -= 2. * game.players[opponent].hand.len() as f32;
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::game::{
+        card_data::{load_cards, starter_deck},
+        deck::Deck,
+        player::Player,
+        tiled::TiledMap,
+        world::World,
+    };
+
+    /// Number of actions played by each test
+    const ACTIONS: usize = 30;
+
+    /// Match of the game on the first map, with the starter decks
+    fn new_match(seed: u64) -> Match {
+        let map = TiledMap::load(Path::new("assets/map1.tmx")).unwrap();
+        let world = World::from_tiled(&map).unwrap();
+        let (cards, errors) = load_cards(Path::new("assets/cards"));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let players = [0, 1].map(|id| {
+            let mut deck = Deck::new(id, starter_deck(&cards), seed + id as u64);
+            deck.shuffle();
+            let mut player = Player::new(id, format!("Player {}", id + 1));
+            player.deck = deck;
+            player
+        });
+        Match::new(world, players)
+    }
+
+    /// AIs of both players, the searches only stop on their iterations
+    fn ais(difficulty: Difficulty, seed: u64) -> [Ai; 2] {
+        [0, 1].map(|player| {
+            let mut ai = Ai::new(player, difficulty, seed + player as u64);
+            ai.budget = SearchBudget {
+                iterations: 20,
+                time: None,
+            };
+            ai
+        })
+    }
+
+    #[test]
+    fn same_seed_same_actions() {
+        for difficulty in Difficulty::ALL {
+            let mut game = new_match(7);
+            let mut first = ais(difficulty, 3);
+            let mut second = ais(difficulty, 3);
+            for _ in 0..ACTIONS {
+                let player = game.turn.active_player;
+                let Some(action) = first[player].choose(&game.clone()) else {
+                    break;
+                };
+                assert_eq!(
+                    second[player].choose(&game.clone()),
+                    Some(action.clone()),
+                    "{} on turn {}",
+                    difficulty,
+                    game.turn.number
+                );
+                game.apply(action).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn chosen_actions_are_valid() {
+        for difficulty in Difficulty::ALL {
+            let mut game = new_match(11);
+            let mut ais = ais(difficulty, 5);
+            for _ in 0..ACTIONS {
+                let Some(action) = ais[game.turn.active_player].choose(&game) else {
+                    break;
+                };
+                assert_eq!(
+                    game.validate(&action),
+                    Ok(()),
+                    "{} chose {:?}",
+                    difficulty,
+                    action
+                );
+                game.apply(action).unwrap();
+            }
+        }
+    }
+}
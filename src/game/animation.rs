@@ -0,0 +1,159 @@
+use macroquad::{math::Vec2, texture::Texture2D};
+
+/// Duration of one loop of the idle clip whatever its number of frames, in seconds
+const IDLE_DURATION: f64 = 1.;
+
+/// Clips a creature can play, see [`Animator`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Clip {
+    Idle,
+    Walk,
+    Attack,
+    Hurt,
+    Die,
+}
+
+impl Clip {
+    pub const ALL: [Clip; 5] = [Clip::Idle, Clip::Walk, Clip::Attack, Clip::Hurt, Clip::Die];
+
+    /// Name of the clip in the file names of its frames (ex: "goblin-walk-0.png")
+    pub fn name(self) -> &'static str {
+        match self {
+            Clip::Idle => "idle",
+            Clip::Walk => "walk",
+            Clip::Attack => "attack",
+            Clip::Hurt => "hurt",
+            Clip::Die => "die",
+        }
+    }
+
+    /// Time during which each of the `nb_frames` frames is shown, in seconds
+    pub fn time_per_frame(self, nb_frames: usize) -> f64 {
+        match self {
+            Clip::Idle => IDLE_DURATION / nb_frames.max(1) as f64,
+            Clip::Walk => 0.1,
+            Clip::Attack | Clip::Hurt => 0.08,
+            Clip::Die => 0.12,
+        }
+    }
+
+    /// If the clip starts again once over, the others stay on their last frame
+    pub fn looping(self) -> bool {
+        matches!(self, Clip::Idle | Clip::Walk)
+    }
+}
+
+/// Frames of a clip and their timing
+pub struct Animation {
+    /// Never empty
+    frames: Vec<Texture2D>,
+    time_per_frame: f64,
+    looping: bool,
+}
+
+impl Animation {
+    /// `frames` must not be empty
+    pub fn new(frames: Vec<Texture2D>, time_per_frame: f64, looping: bool) -> Self {
+        assert!(!frames.is_empty(), "an animation needs at least one frame");
+        Animation {
+            frames,
+            time_per_frame,
+            looping,
+        }
+    }
+
+    /// Duration of one loop, in seconds
+    pub fn duration(&self) -> f64 {
+        self.frames.len() as f64 * self.time_per_frame
+    }
+
+    /// Frame shown `elapsed` seconds after the start of the clip
+    pub fn frame(&self, elapsed: f64) -> &Texture2D {
+        let frame = (elapsed.max(0.) / self.time_per_frame) as usize;
+        if self.looping {
+            &self.frames[frame % self.frames.len()]
+        } else {
+            &self.frames[frame.min(self.frames.len() - 1)]
+        }
+    }
+}
+
+/// Side toward which a creature looks. The frames are drawn facing right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facing {
+    Left,
+    Right,
+}
+
+impl Facing {
+    /// Facing of a creature at `from` looking at `to`, both in world coordinates
+    ///
+    /// `current` is kept when `to` is right above or under `from`.
+    pub fn towards(from: Vec2, to: Vec2, current: Facing) -> Facing {
+        if to.x > from.x {
+            Facing::Right
+        } else if to.x < from.x {
+            Facing::Left
+        } else {
+            current
+        }
+    }
+}
+
+/// Clip played by a creature on the map, changed by the events of the match
+pub struct Animator {
+    /// `img_path` of the creature's card, to find its sprite once it is dead
+    pub img_path: String,
+    pub clip: Clip,
+    /// Time at which the clip started
+    started: f64,
+    /// Time at which the creature goes back to idle, never when `None`
+    until: Option<f64>,
+    pub facing: Facing,
+}
+
+impl Animator {
+    pub fn new(img_path: &str, time: f64) -> Self {
+        Animator {
+            img_path: img_path.to_string(),
+            clip: Clip::Idle,
+            started: time,
+            until: None,
+            facing: Facing::Right,
+        }
+    }
+
+    /// Play `clip` from `time` during `duration` seconds, or until another clip is
+    /// played when `None`
+    pub fn play(&mut self, clip: Clip, time: f64, duration: Option<f64>) {
+        self.clip = clip;
+        self.started = time;
+        self.until = duration.map(|duration| time + duration);
+    }
+
+    /// Go back to idle once the clip is over, a dying creature stays on its last frame
+    pub fn update(&mut self, time: f64) {
+        if self.clip != Clip::Die && self.is_over(time) {
+            self.play(Clip::Idle, time, None);
+        }
+    }
+
+    pub fn is_over(&self, time: f64) -> bool {
+        self.until.is_some_and(|until| time >= until)
+    }
+
+    /// Time since the start of the clip
+    pub fn elapsed(&self, time: f64) -> f64 {
+        time - self.started
+    }
+
+    /// Progression of the clip between 0 and 1, 0 for the clips played for ever
+    pub fn progress(&self, time: f64) -> f64 {
+        match self.until {
+            Some(until) if until > self.started => {
+                ((time - self.started) / (until - self.started)).clamp(0., 1.)
+            }
+            _ => 0.,
+        }
+    }
+}
@@ -1,96 +1,115 @@
+use std::{collections::HashMap, fmt};
+
 use macroquad::{
     color::{Color, BLACK, BLUE, GREEN, LIME, WHITE},
     input::mouse_position,
     math::{vec2, Rect, Vec2},
     shapes::{draw_poly, draw_rectangle, draw_rectangle_lines},
     text::{draw_multiline_text, draw_text},
-    texture::{draw_texture, draw_texture_ex, load_texture, DrawTextureParams, Texture2D},
-    time::get_time,
+    texture::{draw_texture_ex, load_texture, DrawTextureParams, Texture2D},
 };
 
-use super::{effect::Effect, life::HasLife};
+use super::{
+    animation::{Animation, Animator, Clip, Facing},
+    render::{Layer, RenderQueue},
+    rules::Card,
+};
 
-pub enum Card {
-    Creature(CreatureCard),
-    Effect(EffectCard),
-}
+/// Color drawn over the cards that cannot be played
+const UNPLAYABLE_OVERLAY: Color = Color::new(0.3, 0.3, 0.3, 0.6);
 
-pub struct CardBasicInfo {
-    pub name: String, // name of the card
-    // image_front: Path,   // Path to the image on the front of the card
-    // image_back: Path,    // Path to the image on the back of the card
-    pub description: String, // description of the card
-    pub cost: u32,           // cost of the card
-    // pub counter: Option<u32>, // counter before card take effects
-    pub card_color: Color,
-    // pub position: Vec2,
-    // pub size: Vec2,
-}
+/// Size of a creature drawn on the map, the size of the tiles' textures
+const CREATURE_SIZE: Vec2 = Vec2::new(32., 32.);
+/// Picture used for the creatures without a cover
+const DEFAULT_COVER: &str = "creatures/default/default-cover.png";
 
-/// Creature Card
-pub struct CreatureCard {
-    pub basic_info: CardBasicInfo,
-    pub hp_current: u32,
-    pub hp_max: u32,
-    /// Total movement the creature can do
-    pub movement: u32,
-    /// TODO the following will need to have its own struct for animations
-    img_path: &'static str,
-    nb_animation_frame: usize,
-    current_animation_frame: usize,
-    picture: Option<Texture2D>,
-    animation: Vec<Texture2D>,
-    animation_time_per_frame: f64,
-    time_elapsed: f64,
+/// Asset that could not be loaded, a fallback is used instead
+#[derive(Debug, Clone)]
+pub struct MissingAsset {
+    pub path: String,
+    /// What is shown instead
+    pub fallback: &'static str,
 }
 
-impl CreatureCard {
-    pub fn new(
-        basic_info: CardBasicInfo,
-        hp_max: u32,
-        movement: u32,
-        img_path: &'static str,
-        nb_animation_frame: usize, // TODO will added automaticly based on the number of file with the name in creature assets
-        animation_time_per_frame: f64,
-    ) -> Self {
-        CreatureCard {
-            basic_info,
-            hp_current: hp_max,
-            hp_max,
-            movement,
-            img_path,
-            nb_animation_frame,
-            current_animation_frame: 0,
-            picture: None,
-            animation: Vec::new(),
-            animation_time_per_frame,
-            time_elapsed: 0.,
-        }
+impl fmt::Display for MissingAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: not found, {} is used instead",
+            self.path, self.fallback
+        )
     }
+}
 
-    pub async fn load_texture(&mut self) {
-        for i in 0..self.nb_animation_frame {
-            self.animation.push(
-                load_texture(&format!("{}-{}.png", &self.img_path, i))
-                    .await
-                    .unwrap(),
-            );
-        }
-        match load_texture(&format!("{}-cover.png", &self.img_path)).await {
-            Ok(picture_texture) => self.picture = Some(picture_texture),
+/// Textures of a creature, shared by its card and every creature summoned from it
+pub struct CreatureSprite {
+    picture: Texture2D,
+    /// Always has the idle clip, the missing clips are replaced by it
+    clips: HashMap<Clip, Animation>,
+}
+
+impl CreatureSprite {
+    /// Load the textures of a creature, the missing ones are replaced and added to
+    /// `missing`.
+    ///
+    /// The frames of a clip are `<img_path>-<clip>-0.png`, `<img_path>-<clip>-1.png`,
+    /// etc. until a number is missing (see [`Clip::name`]). The idle frames can also
+    /// be `<img_path>-0.png`, etc. Without any idle frame, `<img_path>.png` is shown
+    /// still, and without it the cover is shown instead.
+    pub async fn load(img_path: &str, missing: &mut Vec<MissingAsset>) -> Self {
+        let cover_path = format!("{}-cover.png", img_path);
+        let picture = match load_texture(&cover_path).await {
+            Ok(picture_texture) => picture_texture,
             Err(_) => {
-                self.picture = Some(
-                    load_texture("creatures/default/default-cover.png")
-                        .await
-                        .unwrap(),
-                )
+                missing.push(MissingAsset {
+                    path: cover_path,
+                    fallback: "the default cover",
+                });
+                match load_texture(DEFAULT_COVER).await {
+                    Ok(default_cover) => default_cover,
+                    Err(_) => {
+                        missing.push(MissingAsset {
+                            path: DEFAULT_COVER.to_string(),
+                            fallback: "an empty texture",
+                        });
+                        Texture2D::empty()
+                    }
+                }
+            }
+        };
+
+        let mut clips = HashMap::new();
+        for clip in Clip::ALL {
+            let mut frames = load_frames(&format!("{}-{}", img_path, clip.name())).await;
+            if clip == Clip::Idle && frames.is_empty() {
+                frames = load_frames(img_path).await;
+            }
+            if clip == Clip::Idle && frames.is_empty() {
+                let still_path = format!("{}.png", img_path);
+                match load_texture(&still_path).await {
+                    Ok(still) => frames.push(still),
+                    Err(_) => {
+                        missing.push(MissingAsset {
+                            path: format!("{}-{}-0.png", img_path, clip.name()),
+                            fallback: "the cover",
+                        });
+                        frames.push(picture.clone());
+                    }
+                }
+            }
+
+            if !frames.is_empty() {
+                let time_per_frame = clip.time_per_frame(frames.len());
+                clips.insert(clip, Animation::new(frames, time_per_frame, clip.looping()));
             }
         }
+
+        CreatureSprite { picture, clips }
     }
 
     fn draw_picture(&self, x: f32, y: f32, size: Vec2, color: Color) {
         draw_texture_ex(
-            &self.picture.clone().unwrap(),
+            &self.picture,
             x,
             y,
             color,
@@ -100,180 +119,193 @@ impl CreatureCard {
             },
         );
     }
-    pub fn draw_creature(&mut self, x: f32, y: f32, color: Color) {
-        let time = get_time();
-        let diff = time - self.time_elapsed;
-
-        if diff >= self.animation_time_per_frame {
-            self.current_animation_frame =
-                (self.current_animation_frame + 1) % self.nb_animation_frame;
-            self.time_elapsed = get_time();
-        }
 
-        draw_texture(
-            &self.animation.get(self.current_animation_frame).unwrap(),
-            x,
-            y,
+    /// Animation of the clip, or the idle one when the creature does not have it
+    pub fn animation(&self, clip: Clip) -> &Animation {
+        self.clips
+            .get(&clip)
+            .or_else(|| self.clips.get(&Clip::Idle))
+            .expect("the idle clip is always loaded")
+    }
+
+    /// Queue the frame of the animator's clip at the given time
+    ///
+    /// * `world_pos` : world position of the tile on which the creature stands
+    pub fn queue_creature<'a>(
+        &'a self,
+        queue: &mut RenderQueue<'a>,
+        animator: &Animator,
+        time: f64,
+        world_pos: Vec2,
+        color: Color,
+    ) {
+        let frame = self.animation(animator.clip).frame(animator.elapsed(time));
+
+        // -16 because the tile heigth are 16 and those of the creature img are 32
+        queue.push(
+            world_pos,
+            Layer::Creature,
+            frame,
+            world_pos - vec2(0., 16.),
             color,
+            DrawTextureParams {
+                dest_size: Some(CREATURE_SIZE),
+                flip_x: animator.facing == Facing::Left,
+                ..Default::default()
+            },
         );
     }
 }
 
-impl HasLife for CreatureCard {
-    fn is_alive(&self) -> bool {
-        self.hp_current > 0
-    }
-
-    fn get_life_current(&self) -> u32 {
-        self.hp_current
-    }
-
-    fn get_life_max(&self) -> u32 {
-        self.hp_max
-    }
-
-    fn set_life(&mut self, delta: u32) {
-        self.hp_current = delta;
+/// Frames `<prefix>-0.png`, `<prefix>-1.png`, etc. until a number is missing
+async fn load_frames(prefix: &str) -> Vec<Texture2D> {
+    let mut frames = Vec::new();
+    while let Ok(frame) = load_texture(&format!("{}-{}.png", prefix, frames.len())).await {
+        frames.push(frame);
     }
+    frames
 }
 
-/// Card with effect
-pub struct EffectCard {
-    pub basic_info: CardBasicInfo,
-    pub effect: Effect,
-}
-
-impl EffectCard {
-    pub fn new(basic_info: CardBasicInfo, effect: Effect) -> Self {
-        EffectCard { basic_info, effect }
-    }
+/// Sprites of the creatures, indexed by the `img_path` of their card
+#[derive(Default)]
+pub struct Sprites {
+    creatures: HashMap<String, CreatureSprite>,
+    /// Assets that were not found while loading the sprites
+    pub missing: Vec<MissingAsset>,
 }
 
-impl Card {
-    pub fn get_name(&self) -> &str {
-        match self {
-            Card::Creature(creature) => &creature.basic_info.name,
-            Card::Effect(effect) => &effect.basic_info.name,
+impl Sprites {
+    /// Load the textures of a creature if they are not already loaded
+    ///
+    /// * `img_path` : the `img_path` of the creature's card
+    pub async fn load_creature(&mut self, img_path: &str) {
+        if !self.creatures.contains_key(img_path) {
+            let sprite = CreatureSprite::load(img_path, &mut self.missing).await;
+            self.creatures.insert(img_path.to_string(), sprite);
         }
     }
 
-    /// Load the textures of a card
-    pub async fn load_texture(&mut self) {
-        match self {
-            Card::Creature(creature_card) => creature_card.load_texture().await,
-            Card::Effect(effect_card) => {}
-        }
+    pub fn creature(&self, img_path: &str) -> Option<&CreatureSprite> {
+        self.creatures.get(img_path)
     }
+}
 
-    fn get_basic_info(&self) -> &CardBasicInfo {
-        match self {
-            Card::Creature(creature) => &creature.basic_info,
-            Card::Effect(effect) => &effect.basic_info,
-        }
-    }
+pub fn is_hovered(dimensions: Rect) -> bool {
+    let Rect { x, y, w, h } = dimensions;
+    let (mouse_pos_x, mouse_pos_y) = mouse_position();
 
-    pub fn is_hovered(&self, dimensions: Rect) -> bool {
-        let Rect { x, y, w, h } = dimensions;
-        let (mouse_pos_x, mouse_pos_y) = mouse_position();
+    (mouse_pos_x >= x && mouse_pos_x <= x + w) && (mouse_pos_y >= y && mouse_pos_y <= y + h)
+}
 
-        return (mouse_pos_x >= x && mouse_pos_x <= x + w)
-            && (mouse_pos_y >= y && mouse_pos_y <= y + h);
-    }
+pub fn draw_card(
+    card: &Card,
+    sprites: &Sprites,
+    dimensions: Rect,
+    font_size: f32,
+    font_color: Color,
+) -> Rect {
+    let basic_info = card.get_basic_info();
+    let Rect { x, y, w, h } = dimensions;
+
+    // Name
+    draw_text(
+        &basic_info.name,
+        x + font_size * 0.5,
+        y + font_size,
+        font_size,
+        font_color,
+    );
+
+    // Cost
+    draw_poly(x + w - 17., y + font_size - 3., 8, 10., 0., BLUE);
+    draw_text(
+        &format!("{}", basic_info.cost),
+        x + w - 20.,
+        y + font_size,
+        font_size,
+        font_color,
+    );
+
+    match card {
+        Card::Creature(creature) => {
+            // Cover Picture
+            match sprites.creature(&creature.img_path) {
+                Some(sprite) => {
+                    sprite.draw_picture(x + 6., y + 24., vec2(w - 12., h / 3.), WHITE);
+                    draw_rectangle_lines(x + 6., y + 24., w - 12., h / 3., 2., BLACK);
+                }
+                None => draw_rectangle(x + 6., y + 24., w - 12., h / 3., GREEN),
+            };
+
+            // HP
+            draw_text(
+                &format!("HP: {}/{}", creature.hp_max, creature.hp_max),
+                x + font_size * 0.5,
+                y + h * 0.5,
+                font_size,
+                font_color,
+            );
 
-    pub fn draw_card(&mut self, card: Rect, font_size: f32, font_color: Color) -> Rect {
-        let basic_info = self.get_basic_info();
-        let Rect { x, y, w, h } = card;
-
-        // Name
-        draw_text(
-            &basic_info.name,
-            x + font_size * 0.5,
-            y + font_size,
-            font_size,
-            font_color,
-        );
+            // Attack
+            draw_text(
+                &format!("Attack: {} - Range: {}", creature.attack, creature.range),
+                x + font_size * 0.5,
+                y + h * 0.58,
+                font_size,
+                font_color,
+            );
 
-        // Cost
-        draw_poly(x + w - 17., y + font_size - 3., 8, 10., 0., BLUE);
-        draw_text(
-            &format!("{}", basic_info.cost),
-            x + w - 20.,
-            y + font_size,
-            font_size,
-            font_color,
-        );
+            // Mobility
+            draw_text(
+                &format!("Mobility: {} steps", creature.movement),
+                x + font_size * 0.5,
+                y + h * 0.66,
+                font_size,
+                font_color,
+            );
 
-        match self {
-            Card::Creature(creature) => {
-                // Cover Picture
-                match creature.picture {
-                    Some(_) => {
-                        creature.draw_picture(x + 6., y + 24., vec2(w - 12., h / 3.), WHITE);
-                        draw_rectangle_lines(x + 6., y + 24., w - 12., h / 3., 2., BLACK);
-                    }
-                    None => draw_rectangle(x + 6., y + 24., w - 12., h / 3., GREEN),
-                };
-
-                // HP
-                draw_text(
-                    &format!("HP: {}/{}", creature.hp_current, creature.hp_max),
-                    x + font_size * 0.5,
-                    y + h * 0.5,
-                    font_size,
-                    font_color,
-                );
-
-                // Range
-                draw_text(
-                    &format!("Mobility: {} steps", creature.movement),
-                    x + font_size * 0.5,
-                    y + h * 0.6,
-                    font_size,
-                    font_color,
-                );
-
-                // Description
-                draw_multiline_text(
-                    &creature.basic_info.description,
-                    x + font_size * 0.5,
-                    y + h * 0.7,
-                    font_size * 0.6,
-                    Some(2.),
-                    BLACK,
-                );
-            }
-            Card::Effect(effect) => {
-                // Cover Picture
-                // TODO
-
-                // Effect
-                draw_text(
-                    "Effect: TODO",
-                    x + font_size * 0.5,
-                    y + h * 0.5,
-                    font_size,
-                    font_color,
-                );
-
-                // Description
-                draw_multiline_text(
-                    &effect.basic_info.description,
-                    x + font_size * 0.5,
-                    y + h * 0.6,
-                    font_size * 0.6,
-                    Some(2.),
-                    BLACK,
-                );
-            }
+            // Description
+            draw_multiline_text(
+                &creature.basic_info.description,
+                x + font_size * 0.5,
+                y + h * 0.74,
+                font_size * 0.6,
+                Some(2.),
+                BLACK,
+            );
         }
+        Card::Effect(effect) => {
+            // Cover Picture
+            // TODO
+
+            // Effect
+            draw_text(
+                &format!("Effect: {}", effect.effect),
+                x + font_size * 0.5,
+                y + h * 0.5,
+                font_size,
+                font_color,
+            );
 
-        return Rect { x, y, w, h };
+            // Description
+            draw_multiline_text(
+                &effect.basic_info.description,
+                x + font_size * 0.5,
+                y + h * 0.6,
+                font_size * 0.6,
+                Some(2.),
+                BLACK,
+            );
+        }
     }
+
+    Rect { x, y, w, h }
 }
 
-/// Struct containing a card and its information allowing us to display it.
+/// Information allowing us to display a card of the hand.
+///
+/// The card itself is owned by the rules, see [`super::player::Player::hand`].
 pub struct DisplayedCard {
-    pub card: Card,
     pub scale: f32, // Default 1.
     pub hovered_scale: f32,
     pub base_dimensions: Rect,    // x, y, w, h
@@ -284,14 +316,8 @@ pub struct DisplayedCard {
 }
 
 impl DisplayedCard {
-    pub fn new(
-        card: Card,
-        hovered_scale: f32,
-        base_dimensions: Rect,
-        neighbour_start: f32,
-    ) -> DisplayedCard {
+    pub fn new(hovered_scale: f32, base_dimensions: Rect, neighbour_start: f32) -> DisplayedCard {
         DisplayedCard {
-            card,
             scale: 1.,
             hovered_scale,
             base_dimensions,
@@ -317,9 +343,16 @@ impl DisplayedCard {
         self.scale = DisplayedCard::max(self.scale - speed, 1.);
     }
 
-    pub fn display_card(&mut self, font_size: f32, font_color: Color, is_selected: bool) {
+    pub fn display_card(
+        &mut self,
+        card: &Card,
+        sprites: &Sprites,
+        font_size: f32,
+        font_color: Color,
+        is_selected: bool,
+        is_playable: bool,
+    ) {
         let scale = self.scale;
-        let card = &self.card;
 
         let x = self.base_dimensions.x;
         let mut y = self.base_dimensions.y - (self.current_dimensions.h - self.base_dimensions.h);
@@ -331,7 +364,7 @@ impl DisplayedCard {
         let mut hoverable_surface = self.base_dimensions;
         hoverable_surface.w = self.neighbour_start - self.base_dimensions.x;
 
-        let card_color = card.get_basic_info().card_color;
+        let card_color = Color::from_vec(card.get_basic_info().card_color);
 
         // Draw the zone in which the cursor must be in order to highligth the card
         draw_rectangle_lines(
@@ -343,13 +376,11 @@ impl DisplayedCard {
             card_color,
         );
 
-        if self.card.is_hovered(hoverable_surface) {
-            y = y * 0.6; // Move the card upward to not overlap other cards
-            draw_rectangle(x, y, w, h, card.get_basic_info().card_color); // background
-                                                                          // draw_rectangle_lines(x, y, w, h, 8., if is_selected { GREEN } else { BLUE }); // outline
+        if is_hovered(hoverable_surface) {
+            y *= 0.6; // Move the card upward to not overlap other cards
+            draw_rectangle(x, y, w, h, card_color); // background
 
             // outline
-            // draw_rectangle_lines(x, y, w, h, 8., BLUE);
             if is_selected {
                 draw_rectangle_lines(x, y, w, h, 8., LIME);
             } else {
@@ -357,7 +388,7 @@ impl DisplayedCard {
             }
             self.increase_scale(0.2);
         } else {
-            draw_rectangle(x, y, w, h, card.get_basic_info().card_color); // background
+            draw_rectangle(x, y, w, h, card_color); // background
 
             // outline
             if is_selected {
@@ -369,7 +400,11 @@ impl DisplayedCard {
             self.decrease_scale(0.5);
         }
 
-        self.card
-            .draw_card(Rect { x, y, w, h }, font_size, font_color);
+        draw_card(card, sprites, Rect { x, y, w, h }, font_size, font_color);
+
+        // Grey out the card if it cannot be played
+        if !is_playable {
+            draw_rectangle(x, y, w, h, UNPLAYABLE_OVERLAY);
+        }
     }
 }
@@ -0,0 +1,425 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use glam::Vec4;
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+use super::{
+    effect::{Effect, EffectType},
+    rules::{state::AP_CAP, Card, CardBasicInfo, CreatureCard, EffectCard},
+};
+
+/// Extension of the card files
+pub const CARD_FILE_EXTENSION: &str = "ron";
+/// Number of copies of each card in the starter deck
+pub const STARTER_DECK_COPIES: usize = 4;
+
+/// Card as written in a card file
+///
+/// Example of a card file:
+/// ```ron
+/// (
+///     name: "Goblin",
+///     description: "Vilest of creatures.",
+///     cost: 1,
+///     color: (0.83, 0.69, 0.52, 1.0),
+///     kind: Creature(hp: 4, attack: 2, range: 1, movement: 4, sprite: "creatures/goblin/goblin"),
+/// )
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardDefinition {
+    name: String,
+    #[serde(default)]
+    description: String,
+    cost: u32,
+    /// Background color of the card (r, g, b, a), each between 0 and 1
+    color: (f32, f32, f32, f32),
+    kind: KindDefinition,
+}
+
+#[derive(Debug, Deserialize)]
+enum KindDefinition {
+    Creature(CreatureDefinition),
+    Effect(EffectDefinition),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CreatureDefinition {
+    hp: u32,
+    attack: u32,
+    #[serde(default = "default_range")]
+    range: u32,
+    movement: u32,
+    /// Path of the creature's assets without the frame suffix
+    sprite: String,
+    #[serde(default)]
+    on_death: Option<EffectDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EffectDefinition {
+    effect: EffectType,
+    amount: u32,
+    /// Creature summoned by a summon effect
+    #[serde(default)]
+    token: Option<TokenDefinition>,
+}
+
+/// Creature created by an effect, it is not a card of the deck
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenDefinition {
+    name: String,
+    #[serde(default)]
+    description: String,
+    hp: u32,
+    attack: u32,
+    #[serde(default = "default_range")]
+    range: u32,
+    movement: u32,
+    sprite: String,
+}
+
+fn default_range() -> u32 {
+    1
+}
+
+/// Error in a card file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardError {
+    pub file: PathBuf,
+    /// Path of the wrong field (ex: "kind.Creature.hp") or position of the syntax error
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}: {}", self.file.display(), field, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+/// Parse the content of a card file, `file` is only used in the errors
+pub fn parse_card(file: &Path, source: &str) -> Result<Card, CardError> {
+    let options = ron::Options::default()
+        .with_default_extension(Extensions::UNWRAP_VARIANT_NEWTYPES | Extensions::IMPLICIT_SOME);
+    let definition: CardDefinition = options.from_str(source).map_err(|error| CardError {
+        file: file.to_path_buf(),
+        field: Some(format!("{}", error.position)),
+        message: error.code.to_string(),
+    })?;
+
+    definition
+        .into_card()
+        .map_err(|(field, message)| CardError {
+            file: file.to_path_buf(),
+            field: Some(field),
+            message,
+        })
+}
+
+/// Load every card file of the directory, sorted by file name
+///
+/// The cards with errors are skipped, all the errors are returned so that they can
+/// be fixed at once. The decks and the simulations find the cards by name, a card whose
+/// name is already taken by a previous file is an error.
+pub fn load_cards(dir: &Path) -> (Vec<Card>, Vec<CardError>) {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == CARD_FILE_EXTENSION)
+            })
+            .collect(),
+        Err(error) => {
+            let error = CardError {
+                file: dir.to_path_buf(),
+                field: None,
+                message: error.to_string(),
+            };
+            return (Vec::new(), vec![error]);
+        }
+    };
+    files.sort();
+
+    let mut cards: Vec<Card> = Vec::new();
+    let mut errors = Vec::new();
+    // File of each card name
+    let mut names: HashMap<String, PathBuf> = HashMap::new();
+    for file in files {
+        let card = fs::read_to_string(&file)
+            .map_err(|error| CardError {
+                file: file.clone(),
+                field: None,
+                message: error.to_string(),
+            })
+            .and_then(|source| parse_card(&file, &source))
+            .and_then(|card| match names.get(card.get_name()) {
+                Some(first) => Err(CardError {
+                    file: file.clone(),
+                    field: Some("name".to_string()),
+                    message: format!(
+                        "\"{}\" is already the name of {}",
+                        card.get_name(),
+                        first.display()
+                    ),
+                }),
+                None => Ok(card),
+            });
+        match card {
+            Ok(card) => {
+                names.insert(card.get_name().to_string(), file);
+                cards.push(card);
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+    (cards, errors)
+}
+
+/// Cards of the starter deck, every card is added [`STARTER_DECK_COPIES`] times
+pub fn starter_deck(cards: &[Card]) -> Vec<Card> {
+    (0..STARTER_DECK_COPIES)
+        .flat_map(|_| cards.iter().cloned())
+        .collect()
+}
+
+/// Parse the content of a deck file, `file` is only used in the errors
+///
+/// A deck file lists the name of each card of the deck and its number of copies, the
+/// cards are taken from `cards`:
+/// ```ron
+/// [("Goblin", 4), ("Fire Ball", 2)]
+/// ```
+pub fn parse_deck(file: &Path, source: &str, cards: &[Card]) -> Result<Vec<Card>, CardError> {
+    let error = |field: Option<String>, message: String| CardError {
+        file: file.to_path_buf(),
+        field,
+        message,
+    };
+    let entries: Vec<(String, usize)> = ron::from_str(source)
+        .map_err(|e| error(Some(format!("{}", e.position)), e.code.to_string()))?;
+
+    let mut deck = Vec::new();
+    for (i, (name, copies)) in entries.iter().enumerate() {
+        let Some(card) = cards
+            .iter()
+            .find(|card| card.get_basic_info().name == *name)
+        else {
+            return Err(error(
+                Some(format!("[{}]", i)),
+                format!("no card is named \"{}\"", name),
+            ));
+        };
+        deck.extend(std::iter::repeat_n(card, *copies).cloned());
+    }
+    if deck.is_empty() {
+        return Err(error(None, "the deck has no card".to_string()));
+    }
+    Ok(deck)
+}
+
+/// Load a deck file, see [`parse_deck`]
+pub fn load_deck(file: &Path, cards: &[Card]) -> Result<Vec<Card>, CardError> {
+    let source = fs::read_to_string(file).map_err(|error| CardError {
+        file: file.to_path_buf(),
+        field: None,
+        message: error.to_string(),
+    })?;
+    parse_deck(file, &source, cards)
+}
+
+/// Error on a field: its path and what is wrong with it
+type FieldError = (String, String);
+
+fn field_error(field: &str, message: &str) -> FieldError {
+    (field.to_string(), message.to_string())
+}
+
+impl CardDefinition {
+    fn into_card(self) -> Result<Card, FieldError> {
+        if self.name.trim().is_empty() {
+            return Err(field_error("name", "must not be empty"));
+        }
+        if self.cost > AP_CAP {
+            return Err(field_error(
+                "cost",
+                &format!("must be at most {} (maximum of action points)", AP_CAP),
+            ));
+        }
+        let (r, g, b, a) = self.color;
+        let card_color = Vec4::new(r, g, b, a);
+        if !card_color.cmpge(Vec4::ZERO).all() || !card_color.cmple(Vec4::ONE).all() {
+            return Err(field_error("color", "components must be between 0 and 1"));
+        }
+
+        let basic_info = CardBasicInfo {
+            name: self.name,
+            description: self.description,
+            cost: self.cost,
+            card_color,
+        };
+        match self.kind {
+            KindDefinition::Creature(creature) => Ok(Card::Creature(
+                creature.into_creature_card(basic_info, "kind.Creature")?,
+            )),
+            KindDefinition::Effect(effect) => {
+                let effect = effect.into_effect(card_color, "kind.Effect")?;
+                Ok(Card::Effect(EffectCard::new(basic_info, effect)))
+            }
+        }
+    }
+}
+
+impl CreatureDefinition {
+    fn into_creature_card(
+        self,
+        basic_info: CardBasicInfo,
+        path: &str,
+    ) -> Result<CreatureCard, FieldError> {
+        if self.hp == 0 {
+            return Err(field_error(&format!("{}.hp", path), "must be at least 1"));
+        }
+        if self.sprite.trim().is_empty() {
+            return Err(field_error(
+                &format!("{}.sprite", path),
+                "must not be empty",
+            ));
+        }
+
+        let card_color = basic_info.card_color;
+        let card = CreatureCard::new(
+            basic_info,
+            self.hp,
+            self.attack,
+            self.range,
+            self.movement,
+            &self.sprite,
+        );
+        match self.on_death {
+            Some(on_death) => {
+                let path = format!("{}.on_death", path);
+                Ok(card.with_on_death(on_death.into_effect(card_color, &path)?))
+            }
+            None => Ok(card),
+        }
+    }
+}
+
+impl EffectDefinition {
+    /// `card_color` is given to the tokens
+    fn into_effect(self, card_color: Vec4, path: &str) -> Result<Effect, FieldError> {
+        if self.amount == 0 {
+            return Err(field_error(
+                &format!("{}.amount", path),
+                "must be at least 1",
+            ));
+        }
+
+        match (self.effect, self.token) {
+            (EffectType::Summon, Some(token)) => {
+                let basic_info = CardBasicInfo {
+                    name: token.name,
+                    description: token.description,
+                    cost: 0,
+                    card_color,
+                };
+                let creature = CreatureDefinition {
+                    hp: token.hp,
+                    attack: token.attack,
+                    range: token.range,
+                    movement: token.movement,
+                    sprite: token.sprite,
+                    on_death: None,
+                };
+                let path = format!("{}.token", path);
+                let token = creature.into_creature_card(basic_info, &path)?;
+                Ok(Effect::summon(self.amount, token))
+            }
+            (EffectType::Summon, None) => Err(field_error(
+                &format!("{}.token", path),
+                "is required by summon effects",
+            )),
+            (_, Some(_)) => Err(field_error(
+                &format!("{}.token", path),
+                "is only used by summon effects",
+            )),
+            (effect_type, None) => Ok(Effect::new(effect_type, self.amount)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card_source(name: &str) -> String {
+        format!(
+            r#"(name: "{}", cost: 1, color: (1.0, 1.0, 1.0, 1.0), kind: Effect(effect: Heal, amount: 1))"#,
+            name
+        )
+    }
+
+    #[test]
+    fn syntax_errors_give_the_file_and_the_position() {
+        let dir = std::env::temp_dir().join(format!("cards-syntax-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = "(\n    name: \"Goblin\",\n    cost: one,\n)";
+        fs::write(dir.join("goblin.ron"), source).unwrap();
+
+        let (cards, errors) = load_cards(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(cards.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, dir.join("goblin.ron"));
+        assert_eq!(errors[0].field.as_deref(), Some("3:11"));
+        let shown = errors[0].to_string();
+        assert!(shown.starts_with(&format!("{}: 3:11: ", dir.join("goblin.ron").display())));
+    }
+
+    #[test]
+    fn invalid_fields_give_their_path() {
+        let source = r#"(name: "Goblin", cost: 1, color: (1.0, 1.0, 1.0, 1.0),
+            kind: Creature(hp: 0, attack: 1, movement: 1, sprite: "goblin"))"#;
+        let Err(error) = parse_card(Path::new("goblin.ron"), source) else {
+            panic!("a creature needs some hp");
+        };
+        assert_eq!(error.field.as_deref(), Some("kind.Creature.hp"));
+        assert_eq!(
+            error.to_string(),
+            "goblin.ron: kind.Creature.hp: must be at least 1"
+        );
+    }
+
+    #[test]
+    fn duplicate_names_are_refused() {
+        let dir = std::env::temp_dir().join(format!("cards-duplicates-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, name) in [("a.ron", "Goblin"), ("b.ron", "Goblin"), ("c.ron", "Imp")] {
+            fs::write(dir.join(file), card_source(name)).unwrap();
+        }
+
+        let (cards, errors) = load_cards(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = cards.iter().map(Card::get_name).collect();
+        assert_eq!(names, ["Goblin", "Imp"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, dir.join("b.ron"));
+        assert_eq!(errors[0].field.as_deref(), Some("name"));
+    }
+}
@@ -1,7 +1,257 @@
+use super::rules::{rng::Rng, Card};
+
+/// Number of cards a hand can hold, the cards drawn into a full hand are exiled
+pub const MAX_HAND_SIZE: usize = 10;
+
+/// What happens when a player must draw from an empty deck with nothing left to reshuffle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyDeckRule {
+    /// The player takes damage, increased by 1 each time it happens
+    Fatigue,
+    /// The player loses the match
+    Loss,
+}
+
+/// Result of drawing a card
+pub enum Draw {
+    Card(Card),
+    /// Nothing could be drawn, the player takes the given fatigue damage
+    Fatigue(u32),
+    /// Nothing could be drawn, the player loses
+    Loss,
+}
+
+/// Deck of a player during a match, split into its zones
+///
+/// `NOTE` The top of the draw pile is the end of the vec.
+#[allow(dead_code)]
+#[derive(Clone)]
 pub struct Deck {
     deck_id: u32,
     deck_size: u32,
-    // NOTE: Possiblement remplacer vec par hashmap (simplifie la recherche de carte et l'edit des decks)
-    // cards_unplayed: vec<Card>,
-    // cards_played: vec<Card>,
+    /// Cards left to draw
+    pub draw_pile: Vec<Card>,
+    /// Cards already played or destroyed, they are shuffled back once the draw pile is empty
+    pub discard_pile: Vec<Card>,
+    /// Cards removed from the match for good, like the ones drawn into a full hand
+    pub exile: Vec<Card>,
+    pub empty_deck_rule: EmptyDeckRule,
+    /// Damage of the next fatigue
+    fatigue: u32,
+    rng: Rng,
+}
+
+impl Deck {
+    /// Create a deck whose first card is the first to be drawn
+    ///
+    /// The deck is not shuffled, see [`Deck::shuffle`].
+    pub fn new(deck_id: u32, mut cards: Vec<Card>, seed: u64) -> Self {
+        cards.reverse();
+        Deck {
+            deck_id,
+            deck_size: cards.len() as u32,
+            draw_pile: cards,
+            discard_pile: Vec::new(),
+            exile: Vec::new(),
+            empty_deck_rule: EmptyDeckRule::Fatigue,
+            fatigue: 1,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn with_empty_deck_rule(mut self, rule: EmptyDeckRule) -> Self {
+        self.empty_deck_rule = rule;
+        self
+    }
+
+    /// Number of cards left in the draw pile
+    pub fn len(&self) -> usize {
+        self.draw_pile.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draw_pile.is_empty()
+    }
+
+    /// Shuffle the draw pile with the seeded generator of the deck
+    pub fn shuffle(&mut self) {
+        self.rng.shuffle(&mut self.draw_pile);
+    }
+
+    /// Shuffle the discard pile back into the draw pile
+    pub fn reshuffle_discard(&mut self) {
+        self.draw_pile.append(&mut self.discard_pile);
+        self.shuffle();
+    }
+
+    /// Draw the top card of the draw pile
+    ///
+    /// If the draw pile is empty, the discard pile is shuffled back into it. If both are
+    /// empty, the [`EmptyDeckRule`] of the deck applies.
+    pub fn draw(&mut self) -> Draw {
+        if self.draw_pile.is_empty() && !self.discard_pile.is_empty() {
+            self.reshuffle_discard();
+        }
+
+        match self.draw_pile.pop() {
+            Some(card) => Draw::Card(card),
+            None => match self.empty_deck_rule {
+                EmptyDeckRule::Fatigue => {
+                    let damage = self.fatigue;
+                    self.fatigue += 1;
+                    Draw::Fatigue(damage)
+                }
+                EmptyDeckRule::Loss => Draw::Loss,
+            },
+        }
+    }
+
+    /// Draw `nb` cards into `hand`, the cards that do not fit in it are exiled (see
+    /// [`MAX_HAND_SIZE`])
+    ///
+    /// Return what could not be drawn (fatigue or loss), in order.
+    pub fn draw_into(&mut self, nb: usize, hand: &mut Vec<Card>) -> Vec<Draw> {
+        let mut missed = Vec::new();
+        for _ in 0..nb {
+            match self.draw() {
+                Draw::Card(card) if hand.len() >= MAX_HAND_SIZE => self.exile(card),
+                Draw::Card(card) => hand.push(card),
+                other => missed.push(other),
+            }
+        }
+        missed
+    }
+
+    /// Put a card in the discard pile
+    pub fn discard(&mut self, card: Card) {
+        self.discard_pile.push(card);
+    }
+
+    /// Remove a card from the match
+    pub fn exile(&mut self, card: Card) {
+        self.exile.push(card);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec4;
+
+    use super::*;
+    use crate::game::{
+        effect::{Effect, EffectType},
+        rules::{CardBasicInfo, EffectCard},
+    };
+
+    fn card(name: &str) -> Card {
+        let basic_info = CardBasicInfo {
+            name: name.to_string(),
+            description: String::new(),
+            cost: 0,
+            card_color: Vec4::ONE,
+        };
+        Card::Effect(EffectCard::new(
+            basic_info,
+            Effect::new(EffectType::Damage, 1),
+        ))
+    }
+
+    fn deck(names: &[&str], seed: u64) -> Deck {
+        Deck::new(0, names.iter().map(|name| card(name)).collect(), seed)
+    }
+
+    fn names(cards: &[Card]) -> Vec<&str> {
+        cards.iter().map(Card::get_name).collect()
+    }
+
+    fn drawn_name(draw: Draw) -> String {
+        match draw {
+            Draw::Card(card) => card.get_name().to_string(),
+            Draw::Fatigue(damage) => format!("fatigue {}", damage),
+            Draw::Loss => "loss".to_string(),
+        }
+    }
+
+    #[test]
+    fn draws_from_the_top() {
+        let mut deck = deck(&["a", "b", "c"], 0);
+        assert_eq!(drawn_name(deck.draw()), "a");
+        assert_eq!(drawn_name(deck.draw()), "b");
+        assert_eq!(deck.len(), 1);
+    }
+
+    #[test]
+    fn same_seed_same_shuffle() {
+        let cards: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+        let cards: Vec<&str> = cards.iter().map(String::as_str).collect();
+        let mut first = deck(&cards, 7);
+        let mut second = deck(&cards, 7);
+        let mut other = deck(&cards, 8);
+        first.shuffle();
+        second.shuffle();
+        other.shuffle();
+        assert_eq!(names(&first.draw_pile), names(&second.draw_pile));
+        assert_ne!(names(&first.draw_pile), names(&other.draw_pile));
+    }
+
+    #[test]
+    fn reshuffles_the_discard_pile_when_empty() {
+        let mut deck = deck(&["a"], 0);
+        let drawn = match deck.draw() {
+            Draw::Card(card) => card,
+            _ => panic!("the deck has a card"),
+        };
+        deck.discard(drawn);
+        deck.discard(card("b"));
+        assert!(deck.is_empty());
+
+        let mut hand = Vec::new();
+        assert!(deck.draw_into(2, &mut hand).is_empty());
+        let mut drawn = names(&hand);
+        drawn.sort();
+        assert_eq!(drawn, ["a", "b"]);
+        assert!(deck.discard_pile.is_empty());
+    }
+
+    #[test]
+    fn fatigue_goes_up_on_each_empty_draw() {
+        let mut deck = deck(&[], 0);
+        let draws: Vec<String> = (0..3).map(|_| drawn_name(deck.draw())).collect();
+        assert_eq!(draws, ["fatigue 1", "fatigue 2", "fatigue 3"]);
+    }
+
+    #[test]
+    fn empty_deck_loss() {
+        let mut deck = deck(&["a"], 0).with_empty_deck_rule(EmptyDeckRule::Loss);
+        let mut hand = Vec::new();
+        let missed: Vec<String> = deck
+            .draw_into(2, &mut hand)
+            .into_iter()
+            .map(drawn_name)
+            .collect();
+        assert_eq!(names(&hand), ["a"]);
+        assert_eq!(missed, ["loss"]);
+    }
+
+    #[test]
+    fn cards_drawn_into_a_full_hand_are_exiled() {
+        let mut deck = deck(&["a", "b"], 0);
+        let mut hand = vec![card("held"); MAX_HAND_SIZE - 1];
+        assert!(deck.draw_into(2, &mut hand).is_empty());
+        assert_eq!(hand.len(), MAX_HAND_SIZE);
+        assert_eq!(names(&hand[MAX_HAND_SIZE - 1..]), ["a"]);
+        assert_eq!(names(&deck.exile), ["b"]);
+    }
+
+    #[test]
+    fn exiled_cards_are_never_drawn_again() {
+        let mut deck = deck(&[], 0);
+        deck.discard(card("discarded"));
+        deck.exile(card("exiled"));
+        assert_eq!(names(&deck.exile), ["exiled"]);
+
+        assert_eq!(drawn_name(deck.draw()), "discarded");
+        assert_eq!(drawn_name(deck.draw()), "fatigue 1");
+        assert_eq!(names(&deck.exile), ["exiled"]);
+    }
 }
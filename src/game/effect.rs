@@ -1,5 +1,15 @@
-use super::{card::CreatureCard, life::HasLife, player::Player};
+use std::fmt;
 
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    life::{HasLife, LifeChange},
+    player::PlayerId,
+    rules::{CreatureCard, CreatureId},
+};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
 pub enum EffectType {
     Heal,
     Damage,
@@ -7,32 +17,101 @@ pub enum EffectType {
     Summon,
 }
 
+/// What a card can be played on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Target {
-    Creature(CreatureCard),
-    Player(Player),
+    /// A tile of the map, where creatures are summoned
+    Tile(IVec2),
+    Creature(CreatureId),
+    Player(PlayerId),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Tile(pos) => write!(f, "tile {}", pos),
+            Target::Creature(id) => write!(f, "creature #{}", id),
+            Target::Player(player) => write!(f, "player {}", player + 1),
+        }
+    }
 }
 
+/// Effect of a card once its targets have been chosen, ready to be resolved
 pub struct TargettedCardEffect {
-    target: Target,
-    nb: u32, // num of damage, heal, summon, etc.
+    pub effect: Effect,
+    /// Targets in the order described by [`Effect::nb_targets`]
+    pub targets: Vec<Target>,
 }
 
+#[derive(Clone)]
 pub struct Effect {
     pub effect_type: EffectType,
     pub nb: u32, // num of damage, heal, summon, etc.
+    /// Creature created by a summon effect
+    pub token: Option<Box<CreatureCard>>,
 }
 
 impl Effect {
-    fn new(effect_type: EffectType, nb: u32) -> Self {
-        Effect { effect_type, nb }
+    pub fn new(effect_type: EffectType, nb: u32) -> Self {
+        Effect {
+            effect_type,
+            nb,
+            token: None,
+        }
+    }
+
+    /// Effect summoning `nb` copies of the token
+    pub fn summon(nb: u32, token: CreatureCard) -> Self {
+        Effect {
+            effect_type: EffectType::Summon,
+            nb,
+            token: Some(Box::new(token)),
+        }
+    }
+
+    /// Number of targets to choose before the effect can be resolved
+    ///
+    /// * Heal and Damage: the creature or player affected
+    /// * Move: the creature to move then the tile where it goes
+    /// * Summon: one tile for each token
+    pub fn nb_targets(&self) -> usize {
+        match self.effect_type {
+            EffectType::Heal | EffectType::Damage => 1,
+            EffectType::Move => 2,
+            EffectType::Summon => self.nb as usize,
+        }
+    }
+
+    /// Choose the targets of the effect
+    pub fn on(self, targets: Vec<Target>) -> TargettedCardEffect {
+        TargettedCardEffect {
+            effect: self,
+            targets,
+        }
     }
 
-    pub fn affect_target<T: HasLife>(&self, target: &mut T) {
+    /// Apply the effect on something with life and return how its life changed
+    ///
+    /// `NOTE` Move and Summon act on the board, they are resolved by the match instead.
+    pub fn affect_target<T: HasLife>(&self, target: &mut T) -> Option<LifeChange> {
+        match self.effect_type {
+            EffectType::Heal => Some(LifeChange::Healed(target.heal(self.nb))),
+            EffectType::Damage => Some(LifeChange::Damaged(target.damage(self.nb))),
+            EffectType::Move | EffectType::Summon => None,
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.effect_type {
-            EffectType::Heal => target.heal(self.nb),
-            EffectType::Damage => target.damage(self.nb),
-            EffectType::Move => todo!(),
-            EffectType::Summon => todo!(),
+            EffectType::Heal => write!(f, "Heal {}", self.nb),
+            EffectType::Damage => write!(f, "Deal {} damage", self.nb),
+            EffectType::Move => write!(f, "Move {} steps", self.nb),
+            EffectType::Summon => match &self.token {
+                Some(token) => write!(f, "Summon {} {}", self.nb, token.basic_info.name),
+                None => write!(f, "Summon {}", self.nb),
+            },
         }
     }
 }
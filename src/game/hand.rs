@@ -1,17 +1,26 @@
 use macroquad::{
     color::Color,
-    math::{IVec2, Rect},
+    math::Rect,
     window::{screen_height, screen_width},
 };
 
-use super::card::{Card, CreatureCard, DisplayedCard};
+use super::{
+    card::{DisplayedCard, Sprites},
+    effect::Target,
+    player::Player,
+};
 
-/// Represents a player's hand
+/// Represents a player's hand on screen
+///
+/// The cards themselves belong to the rules ([`super::player::Player::hand`]), this only
+/// keeps what is needed to display them and to know which one the player interacts with.
 pub struct Hand {
-    /// Current cards in hand
+    /// Display information of the cards in hand, in the same order as the cards of the rules
     pub card_in_hands: Vec<DisplayedCard>,
     /// Selected card by the player, ready to be played. Index between -1 (represents none hovered) and the length of card_in_hands
     pub selected_card: i8,
+    /// Targets already chosen for the selected card, in order
+    pub chosen_targets: Vec<Target>,
     /// Hovered card by the cursor. Index between -1 (represents none hovered) and the length of card_in_hands
     pub hovered_card: i8,
     // The scale of hovered cards
@@ -31,6 +40,7 @@ impl Hand {
         Hand {
             card_in_hands: Vec::new(),
             selected_card: -1,
+            chosen_targets: Vec::new(),
             hovered_card: -1,
             hovered_scale,
             card_dimensions,
@@ -39,24 +49,6 @@ impl Hand {
         }
     }
 
-    pub async fn add_card(&mut self, mut card: Card) {
-        // Both base_dimensions and neighbour_start are set to 0. because they will be replace adjusted automatically in update_card_to_screen
-        let base_dimensions = Rect {
-            x: 0.,
-            y: 0.,
-            w: 0.,
-            h: 0.,
-        };
-        let neighbour_start = 0.;
-
-        card.load_texture().await;
-
-        let new_card_to_display =
-            DisplayedCard::new(card, self.hovered_scale, base_dimensions, neighbour_start);
-
-        self.card_in_hands.push(new_card_to_display);
-    }
-
     /// Return if a card is hovered
     pub fn card_is_hovered(&self) -> bool {
         self.hovered_card != -1
@@ -67,10 +59,20 @@ impl Hand {
         self.selected_card != -1
     }
 
+    /// Return the index of the selected card if any
+    pub fn selected(&self) -> Option<usize> {
+        if self.card_is_selected() {
+            Some(self.selected_card as usize)
+        } else {
+            None
+        }
+    }
+
     /// Set the currently hovered card as the selected card
     ///
     /// If the card is already selected then it is not the selected card anymore.
     pub fn select_hovered_card(&mut self) {
+        self.chosen_targets.clear();
         self.selected_card = if self.hovered_card == self.selected_card {
             -1
         } else {
@@ -78,31 +80,30 @@ impl Hand {
         };
     }
 
-    /// Select the target of a given card
-    ///
-    /// TODO:
-    /// Change the effect of the target based on the card type.
-    /// For example, a creature card's target would be where the creature is summoned and a effect
-    /// card's target would be the target of the spell which must be an entity (creature, player, etc.)
-    // fn select_card_target(&self, ctx: &Context) {
-    //     let mouse_in_world = ctx.camera.screen_to_world(mouse_position().into());
-    // }
-
-    /// Play a card that is hovered
-    ///
-    /// If no card is hovered, nothing is done without panicking
-    pub async fn play_card(&mut self, creatures: &mut Vec<(CreatureCard, IVec2)>, pos: IVec2) {
-        assert!(self.selected_card != -1);
-
-        let card = self.card_in_hands.remove(self.selected_card as usize);
-        match card.card {
-            Card::Creature(creature_card) => creatures.push((creature_card, pos)),
-            Card::Effect(effect_card) => todo!(),
-        }
-
+    /// Unselect the selected card, usually once it has been played
+    pub fn unselect(&mut self) {
+        self.chosen_targets.clear();
         self.selected_card = -1;
+    }
 
-        self.update_card_to_screen();
+    /// Keep as many displayed cards as there are cards in the hand of the rules
+    fn sync_with(&mut self, nb_cards: usize) {
+        if self.card_in_hands.len() == nb_cards {
+            return;
+        }
+
+        // Both base_dimensions and neighbour_start are set to 0. because they will be adjusted automatically in update_card_to_screen
+        let base_dimensions = Rect {
+            x: 0.,
+            y: 0.,
+            w: 0.,
+            h: 0.,
+        };
+        self.card_in_hands.truncate(nb_cards);
+        while self.card_in_hands.len() < nb_cards {
+            self.card_in_hands
+                .push(DisplayedCard::new(self.hovered_scale, base_dimensions, 0.));
+        }
     }
 
     /// Update the size and position of the screen to fit the screen size
@@ -117,39 +118,52 @@ impl Hand {
             self.card_dimensions = (current_w * 0.2, current_h * 0.3);
             let (new_w, new_h) = self.card_dimensions;
 
-            let mut i = 1;
-            for card in self.card_in_hands.iter_mut() {
+            for (i, card) in (1..).zip(self.card_in_hands.iter_mut()) {
                 card.base_dimensions.x = (card.base_dimensions.w * 0.3) * i as f32;
                 card.base_dimensions.y = current_h * 0.9;
                 card.base_dimensions.w = new_w;
                 card.base_dimensions.h = new_h;
                 card.neighbour_start = (card.base_dimensions.w * 0.3) * (i + 1) as f32;
-                i += 1;
             }
 
             // Update the last card to make it completely hoverable
-            let last_card = self.card_in_hands.last_mut();
-            match last_card {
-                Some(c) => c.neighbour_start = c.base_dimensions.x + c.base_dimensions.w,
-                None => (),
+            if let Some(c) = self.card_in_hands.last_mut() {
+                c.neighbour_start = c.base_dimensions.x + c.base_dimensions.w;
             }
         }
     }
 
-    /// Display the card in hand to the player's screen
+    /// Display the card in hand of the player to the screen
+    ///
+    /// The cards the player cannot afford are greyed out.
     ///
     /// `NOTE` Currently update the hovered card here to not have to make another
     /// method that would iterate over the cards again to find the hovered one.
-    pub fn display_hand(&mut self, font_size: f32, font_color: Color) {
+    pub fn display_hand(
+        &mut self,
+        player: &Player,
+        sprites: &Sprites,
+        font_size: f32,
+        font_color: Color,
+    ) {
         let mut hovered_card: i8 = -1;
 
+        let cards = &player.hand;
+        self.sync_with(cards.len());
         self.update_card_to_screen();
 
-        for (i, card) in self.card_in_hands.iter_mut().enumerate() {
-            card.display_card(font_size, font_color, self.selected_card == i as i8);
+        for (i, (displayed, card)) in self.card_in_hands.iter_mut().zip(cards).enumerate() {
+            displayed.display_card(
+                card,
+                sprites,
+                font_size,
+                font_color,
+                self.selected_card == i as i8,
+                player.can_afford(card),
+            );
 
             // Keeps the hovered card updated
-            if card.scale > 1. {
+            if displayed.scale > 1. {
                 hovered_card = i as i8;
             }
         }
@@ -1,8 +1,11 @@
 use macroquad::prelude::*;
 
-use crate::{game::ui::Settings, Context, CAM_SPEED};
+use crate::{
+    game::{rules::Action, ui::Settings},
+    Context, CAM_SPEED,
+};
 
-pub const KEY_MAPPINGS: [(&str, &str); 10] = [
+pub const KEY_MAPPINGS: [(&str, &str); 13] = [
     ("[arrows][W/A/S/D]", "Control the camera"),
     ("[LeftClick + mouse mouvement]", "Control the camera"),
     ("[R]", "Reset Camera"),
@@ -13,11 +16,17 @@ pub const KEY_MAPPINGS: [(&str, &str); 10] = [
     ("[T]", "Switch theme"),
     ("[Mousewheel UP]", "Zoom"),
     ("[Mousewheel Down]", "Unzoom"),
+    ("[N]", "Go to the next phase"),
+    ("[E]", "End the turn"),
+    (
+        "[LeftClick on a creature]",
+        "Select it, then click a tile or an enemy",
+    ),
 ];
 
 /// Apply the input given by the user.
 ///
-pub async fn apply_input(ctx: &mut Context, settings: &mut Settings) {
+pub fn apply_input(ctx: &mut Context, settings: &mut Settings) {
     #[cfg_attr(any(), rustfmt::skip)]
     { // Camera related //
     // Camera mouvements with keyboard
@@ -57,19 +66,33 @@ pub async fn apply_input(ctx: &mut Context, settings: &mut Settings) {
     if is_key_pressed(KeyCode::T)      { settings.switch_theme(); }
     }
 
+    // The AI plays its own turn
+    if !ctx.is_user_turn() {
+        return;
+    }
+
     #[cfg_attr(any(), rustfmt::skip)]
     { // Game related //
     // Card selection
     if is_mouse_button_pressed(MouseButton::Left) && ctx.hand.card_is_hovered() { 
         ctx.hand.select_hovered_card();
+        ctx.selected_creature = None;
     }
 
-    // Playing card
-    if ctx.hand.card_is_selected() &&
-       is_mouse_button_pressed(MouseButton::Left) &&
-       ctx.world.cursor_within_map(&ctx.camera)
-    {
-        ctx.hand.play_card(&mut ctx.creatures, ctx.world.cursor_position(&ctx.camera)).await;
+    // Turn
+    if is_key_pressed(KeyCode::N) { ctx.apply_action(Action::NextPhase); }
+    if is_key_pressed(KeyCode::E) { ctx.apply_action(Action::EndTurn); }
+
+    // Choosing the targets of the selected card, it is played once all of them are chosen
+    if let Some(card) = ctx.hand.selected() {
+        if is_mouse_button_pressed(MouseButton::Left) && !ctx.hand.card_is_hovered() {
+            if let Some(target) = ctx.target_under_cursor(card) {
+                ctx.choose_target(card, target);
+            }
+        }
+    } else if is_mouse_button_pressed(MouseButton::Left) && !ctx.hand.card_is_hovered() {
+        // Selecting and moving the creatures on the board
+        ctx.click_board();
     }
     }
 }
@@ -1,5 +1,23 @@
 use std::cmp;
 
+/// Result of dealing damage to something with life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DamageOutcome {
+    /// Life actually lost
+    pub dealt: u32,
+    /// Damage exceeding the life that was left
+    pub overkill: u32,
+    /// The damage brought the life to 0
+    pub died: bool,
+}
+
+/// Change of life caused by an effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifeChange {
+    Healed(u32),
+    Damaged(DamageOutcome),
+}
+
 #[allow(dead_code)]
 pub trait HasLife {
     fn is_alive(&self) -> bool;
@@ -7,12 +25,79 @@ pub trait HasLife {
     fn get_life_max(&self) -> u32;
     fn set_life(&mut self, delta: u32);
 
-    fn heal(&mut self, delta: u32) {
-        let new_life = cmp::min(self.get_life_max(), self.get_life_current() + delta);
+    /// Heal without exceeding the maximum of life
+    ///
+    /// Return the life actually gained.
+    fn heal(&mut self, delta: u32) -> u32 {
+        let current = self.get_life_current();
+        let new_life = cmp::min(self.get_life_max(), current.saturating_add(delta));
         self.set_life(new_life);
+        new_life.saturating_sub(current)
+    }
+
+    /// Deal damage without going under 0
+    fn damage(&mut self, delta: u32) -> DamageOutcome {
+        let current = self.get_life_current();
+        let dealt = cmp::min(current, delta);
+        self.set_life(current - dealt);
+
+        DamageOutcome {
+            dealt,
+            overkill: delta - dealt,
+            died: current > 0 && dealt == current,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Something with 10 life at most
+    struct Dummy(u32);
+
+    impl HasLife for Dummy {
+        fn is_alive(&self) -> bool {
+            self.0 > 0
+        }
+        fn get_life_current(&self) -> u32 {
+            self.0
+        }
+        fn get_life_max(&self) -> u32 {
+            10
+        }
+        fn set_life(&mut self, delta: u32) {
+            self.0 = delta;
+        }
+    }
+
+    #[test]
+    fn damage_saturates_at_zero() {
+        let mut dummy = Dummy(3);
+        let outcome = dummy.damage(5);
+        assert_eq!(
+            outcome,
+            DamageOutcome {
+                dealt: 3,
+                overkill: 2,
+                died: true
+            }
+        );
+        assert!(!dummy.is_alive());
+
+        // Dying only happens once
+        let outcome = dummy.damage(1);
+        assert_eq!(
+            (outcome.dealt, outcome.overkill, outcome.died),
+            (0, 1, false)
+        );
     }
 
-    fn damage(&mut self, delta: u32) {
-        self.set_life(self.get_life_current() - delta);
+    #[test]
+    fn heal_stops_at_the_maximum() {
+        let mut dummy = Dummy(8);
+        assert_eq!(dummy.heal(5), 2);
+        assert_eq!(dummy.get_life_current(), 10);
+        assert_eq!(dummy.heal(u32::MAX), 0);
     }
 }
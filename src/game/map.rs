@@ -0,0 +1,239 @@
+use std::{fs, path::Path};
+
+use macroquad::{
+    color::Color,
+    file::load_string,
+    math::{ivec2, vec2, IVec2, Rect, Vec2},
+    texture::{load_texture, DrawTextureParams, FilterMode, Texture2D},
+};
+
+use super::{
+    card::MissingAsset,
+    render::{Layer, RenderQueue},
+    tiled::{flips, MapError, TiledMap},
+    world::{map_point_to_world, map_to_world, World},
+};
+
+/// Extensions of the map files: saved by Tiled (TMX) or exported in JSON
+pub const MAP_EXTENSIONS: [&str; 2] = ["tmx", "json"];
+
+/// File names of the maps of the folder, sorted
+pub fn list_maps(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut maps: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| MAP_EXTENSIONS.iter().any(|map_ext| ext == *map_ext))
+        })
+        .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    maps.sort();
+    maps
+}
+
+/// Load a map and its external tilesets by its path in the assets folder (ex: "map1.tmx")
+///
+/// The missing images of the tilesets are added to `missing`, the map is still loaded.
+pub async fn load_map(
+    name: &str,
+    missing: &mut Vec<MissingAsset>,
+) -> Result<(World, MapView), MapError> {
+    let source = load_string(name)
+        .await
+        .map_err(|_| MapError::MissingFile(name.to_string()))?;
+    let mut map = TiledMap::parse(name, &source)?;
+
+    // NOTE `TiledMap::load` reads the files directly, macroquad also finds them on the web
+    let dir = Path::new(name).parent().unwrap_or(Path::new(""));
+    for (index, source) in map.external_tilesets()? {
+        let file = dir.join(&source).to_string_lossy().into_owned();
+        let xml = load_string(&file)
+            .await
+            .map_err(|_| MapError::MissingFile(file.clone()))?;
+        map.embed_tileset(index, &xml)?;
+    }
+
+    let world = World::from_tiled(&map)?;
+    let view = MapView::load(map, &dir.to_string_lossy(), missing).await;
+    Ok((world, view))
+}
+
+/// Map made with Tiled as drawn on screen, the rules only know its [`super::world::World`]
+pub struct MapView {
+    map: TiledMap,
+    /// Image of each tileset of the map, `None` when it could not be loaded
+    images: Vec<Option<Texture2D>>,
+}
+
+impl MapView {
+    /// Load the images of the tilesets. The missing ones are added to `missing` and
+    /// their tiles are not drawn.
+    ///
+    /// * `dir` : folder of the map file, the paths of the images are relative to it
+    pub async fn load(map: TiledMap, dir: &str, missing: &mut Vec<MissingAsset>) -> Self {
+        let mut images = Vec::with_capacity(map.tilesets.len());
+        for tileset in &map.tilesets {
+            let Some(image) = &tileset.image else {
+                // NOTE the tilesets made of one image per tile are not supported
+                missing.push(MissingAsset {
+                    path: format!("image of the tileset \"{}\"", tileset.name),
+                    fallback: "nothing",
+                });
+                images.push(None);
+                continue;
+            };
+
+            let path = Path::new(dir).join(image).to_string_lossy().into_owned();
+            match load_texture(&path).await {
+                Ok(texture) => {
+                    // Linear filtering would bleed the neighbouring tiles of the image
+                    texture.set_filter(FilterMode::Nearest);
+                    images.push(Some(texture));
+                }
+                Err(_) => {
+                    missing.push(MissingAsset {
+                        path,
+                        fallback: "nothing",
+                    });
+                    images.push(None);
+                }
+            }
+        }
+
+        MapView { map, images }
+    }
+
+    /// Queue the tiles and the tile objects of every visible layer, in the order of the
+    /// layers
+    ///
+    /// * `tint` : offset and color of the tiles of a cell, to highlight them
+    pub fn queue<'a>(&'a self, queue: &mut RenderQueue<'a>, tint: impl Fn(IVec2) -> (Vec2, Color)) {
+        let width = self.map.width.max(1) as i32;
+        // The game draws the top face of the block tiles on their cell while Tiled aligns
+        // the bottom of the tiles with the bottom of their cell, one cell lower
+        let cell_shift = vec2(0., self.map.tileheight as f32);
+
+        for (index, layer) in self.map.layers.iter().enumerate() {
+            if !layer.visible {
+                continue;
+            }
+            let layer_offset = vec2(layer.offsetx, layer.offsety);
+
+            for (i, gid) in layer.data.iter().enumerate() {
+                let cell = ivec2(i as i32 % width, i as i32 / width);
+                let anchor = map_to_world(cell);
+                let (offset, mut color) = tint(cell);
+                color.a *= layer.opacity;
+
+                let bottom = anchor.y + self.map.tileheight as f32 + cell_shift.y;
+                let tile_height = self.tile_size(*gid).map_or(0., |size| size.y);
+                let pos = vec2(anchor.x, bottom - tile_height) + layer_offset + offset;
+                self.queue_tile(queue, *gid, Layer::Map(index), anchor, pos, None, color);
+            }
+
+            for object in layer.objects.iter().filter(|object| object.visible) {
+                let Some(gid) = object.gid else {
+                    continue;
+                };
+                let Some(index_of_tileset) = self.map.tileset_index(gid) else {
+                    continue;
+                };
+                let tileset = &self.map.tilesets[index_of_tileset];
+
+                // Isometric objects are placed in tiles of `tileheight` pixels on each axis
+                let map_pos = vec2(object.x, object.y) / self.map.tileheight as f32;
+                let point = map_point_to_world(map_pos) + cell_shift + layer_offset;
+                let size = if object.width > 0. && object.height > 0. {
+                    vec2(object.width, object.height)
+                } else {
+                    vec2(tileset.tilewidth as f32, tileset.tileheight as f32)
+                };
+                let pos = point - alignment(tileset.objectalignment.as_deref()) * size;
+                // Standing on the cell whose bottom corner is at `map_pos`
+                let anchor = map_point_to_world(map_pos - Vec2::ONE);
+
+                let color = Color::new(1., 1., 1., layer.opacity);
+                self.queue_tile(
+                    queue,
+                    gid,
+                    Layer::Map(index),
+                    anchor,
+                    pos,
+                    Some(size),
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Size of the tile of the global id in its tileset image, `None` for empty cells
+    fn tile_size(&self, gid: u32) -> Option<Vec2> {
+        let (tileset, _) = self.map.tile(gid)?;
+        Some(vec2(tileset.tilewidth as f32, tileset.tileheight as f32))
+    }
+
+    /// Queue the tile of the global id with its top left corner at `pos`, before the
+    /// tile offset of its tileset. Nothing is queued for empty cells.
+    #[allow(clippy::too_many_arguments)]
+    fn queue_tile<'a>(
+        &'a self,
+        queue: &mut RenderQueue<'a>,
+        gid: u32,
+        layer: Layer,
+        anchor: Vec2,
+        pos: Vec2,
+        size: Option<Vec2>,
+        color: Color,
+    ) {
+        let Some(index) = self.map.tileset_index(gid) else {
+            return;
+        };
+        let (Some(texture), Some((tileset, id))) = (&self.images[index], self.map.tile(gid)) else {
+            return;
+        };
+
+        let (x, y) = tileset.tile_origin(id);
+        let (flip_x, flip_y) = flips(gid);
+        let tile_offset = vec2(tileset.tileoffset.x as f32, tileset.tileoffset.y as f32);
+        queue.push(
+            anchor,
+            layer,
+            texture,
+            pos + tile_offset,
+            color,
+            DrawTextureParams {
+                dest_size: size,
+                source: Some(Rect::new(
+                    x as f32,
+                    y as f32,
+                    tileset.tilewidth as f32,
+                    tileset.tileheight as f32,
+                )),
+                flip_x,
+                flip_y,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// Point of a tile object placed at its position, as a fraction of its size from its
+/// top left corner
+fn alignment(objectalignment: Option<&str>) -> Vec2 {
+    match objectalignment {
+        Some("topleft") => vec2(0., 0.),
+        Some("top") => vec2(0.5, 0.),
+        Some("topright") => vec2(1., 0.),
+        Some("left") => vec2(0., 0.5),
+        Some("center") => vec2(0.5, 0.5),
+        Some("right") => vec2(1., 0.5),
+        Some("bottomleft") => vec2(0., 1.),
+        Some("bottomright") => vec2(1., 1.),
+        // "bottom" and "unspecified", which is "bottom" on isometric maps
+        _ => vec2(0.5, 1.),
+    }
+}
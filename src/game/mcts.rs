@@ -0,0 +1,276 @@
+//! Monte Carlo tree search over clones of the match, used by [`super::ai::Difficulty::Mcts`].
+//!
+//! Each iteration plays a clone of the match from the current state: down the tree of
+//! the actions already explored, then with random actions (the playout). The action
+//! explored the most from the current state is chosen.
+
+use std::time::{Duration, Instant};
+
+use glam::IVec2;
+
+use super::{
+    ai::evaluate,
+    effect::Target,
+    player::PlayerId,
+    rules::{rng::Rng, Action, Match, Phase},
+};
+
+/// Exploration constant of UCB1, higher values explore the less visited actions more
+const EXPLORATION: f32 = 1.4;
+/// Number of random actions of a playout before the state is evaluated
+const PLAYOUT_ACTIONS: usize = 40;
+/// Random targets tried for each target of a card in the playouts
+const TARGET_SAMPLES: usize = 8;
+/// Difference of [`evaluate`] worth about 73% of winning chances
+const SCORE_SCALE: f32 = 20.;
+
+/// Limits of a search, it stops as soon as one of them is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchBudget {
+    pub iterations: u32,
+    /// `None` for searches only limited by their iterations, which are reproducible
+    pub time: Option<Duration>,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        SearchBudget {
+            iterations: 400,
+            time: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Action explored from a state of the search tree
+struct Node {
+    /// Action leading to this node, `None` for the root
+    action: Option<Action>,
+    /// Player who did the action
+    player: PlayerId,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Legal actions not explored yet, listed on the first visit
+    untried: Option<Vec<Action>>,
+    visits: u32,
+    /// Sum of the rewards of the playouts for `player`, between 0 and 1 each
+    reward: f32,
+}
+
+impl Node {
+    fn new(action: Option<Action>, player: PlayerId, parent: Option<usize>) -> Self {
+        Node {
+            action,
+            player,
+            parent,
+            children: Vec::new(),
+            untried: None,
+            visits: 0,
+            reward: 0.,
+        }
+    }
+
+    /// Upper confidence bound of the node, the child with the highest one is explored
+    fn ucb(&self, parent_visits: u32) -> f32 {
+        if self.visits == 0 {
+            return f32::INFINITY;
+        }
+        let visits = self.visits as f32;
+        self.reward / visits + EXPLORATION * ((parent_visits as f32).ln() / visits).sqrt()
+    }
+}
+
+/// Search the best action of the active player
+///
+/// The AI does not know the cards of the opponent nor the order of the decks: each
+/// iteration shuffles them, see `determinize`. The search is reproducible for a given
+/// state and `rng` when the budget has no time limit.
+pub fn search(game: &Match, budget: SearchBudget, rng: &mut Rng) -> Action {
+    let player = game.turn.active_player;
+    let started = Instant::now();
+    let mut nodes = vec![Node::new(None, Match::opponent(player), None)];
+
+    for _ in 0..budget.iterations {
+        if budget.time.is_some_and(|time| started.elapsed() >= time) {
+            break;
+        }
+
+        let mut state = game.clone();
+        determinize(&mut state, player, rng);
+
+        // Selection, down the explored actions
+        let mut node = 0;
+        loop {
+            if nodes[node].untried.is_none() {
+                nodes[node].untried = Some(state.legal_actions());
+            }
+            if nodes[node].untried.as_ref().is_some_and(|u| !u.is_empty())
+                || nodes[node].children.is_empty()
+            {
+                break;
+            }
+            let parent_visits = nodes[node].visits;
+            let best = nodes[node].children.iter().copied().max_by(|a, b| {
+                nodes[*a]
+                    .ucb(parent_visits)
+                    .total_cmp(&nodes[*b].ucb(parent_visits))
+            });
+            let Some(child) = best else {
+                break;
+            };
+            // The shuffled cards may not allow the action of another iteration
+            let action = nodes[child].action.clone().unwrap_or(Action::NextPhase);
+            if state.apply(action).is_err() {
+                break;
+            }
+            node = child;
+        }
+
+        // Expansion, one new action
+        let untried = nodes[node].untried.as_mut().filter(|u| !u.is_empty());
+        if let Some(untried) = untried {
+            let action = untried.swap_remove(rng.below(untried.len()));
+            let actor = state.turn.active_player;
+            if state.apply(action.clone()).is_ok() {
+                nodes.push(Node::new(Some(action), actor, Some(node)));
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                node = child;
+            }
+        }
+
+        // Playout
+        for _ in 0..PLAYOUT_ACTIONS {
+            if state.is_over() {
+                break;
+            }
+            let action = playout_action(&state, rng);
+            if state.apply(action).is_err() {
+                break;
+            }
+        }
+
+        // Backpropagation
+        let rewards = [0, 1].map(|player| reward(&state, player));
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            node.reward += rewards[node.player];
+            current = node.parent;
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .max_by_key(|child| nodes[**child].visits)
+        .and_then(|child| nodes[*child].action.clone())
+        .unwrap_or(Action::NextPhase)
+}
+
+/// Random action of the active player for the playouts
+///
+/// Picking among [`Match::legal_actions`] would be too slow, so would listing the valid
+/// targets or the reachable tiles: each card gets a few random targets checked by the
+/// rules and each creature a single random step.
+fn playout_action(game: &Match, rng: &mut Rng) -> Action {
+    let player = game.active_player();
+    let own_creatures = game
+        .creatures
+        .iter()
+        .filter(|creature| creature.owner == game.turn.active_player);
+    let mut actions = vec![Action::NextPhase];
+
+    match game.turn.phase {
+        Phase::Main => {
+            for (card, in_hand) in player.hand.iter().enumerate() {
+                if !player.can_afford(in_hand) {
+                    continue;
+                }
+                let mut targets = Vec::new();
+                while targets.len() < in_hand.nb_targets() {
+                    let found = (0..TARGET_SAMPLES).find_map(|_| {
+                        let mut sample = targets.clone();
+                        sample.push(random_target(game, rng));
+                        game.check_targets(card, &sample).is_ok().then_some(sample)
+                    });
+                    match found {
+                        Some(found) => targets = found,
+                        None => break,
+                    }
+                }
+                if targets.len() == in_hand.nb_targets() {
+                    actions.push(Action::PlayCard { card, targets });
+                }
+            }
+            for creature in own_creatures {
+                let next: Vec<IVec2> = game.world.neighbors(creature.pos).collect();
+                if next.is_empty() {
+                    continue;
+                }
+                let to = next[rng.below(next.len())];
+                let can_step = game.world.tile_at(to).is_some_and(|tile| {
+                    tile.properties.walkable
+                        && tile.properties.movement_cost <= creature.movement_left
+                });
+                if can_step && game.creature_at(to).is_none() {
+                    actions.push(Action::MoveCreature {
+                        creature: creature.id,
+                        to,
+                    });
+                }
+            }
+        }
+        Phase::Combat => {
+            for creature in own_creatures {
+                let targets = game.attack_targets(creature.id);
+                if !targets.is_empty() {
+                    actions.push(Action::Attack {
+                        attacker: creature.id,
+                        target: targets[rng.below(targets.len())],
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    actions.swap_remove(rng.below(actions.len()))
+}
+
+/// Any tile, creature or player of the match, each kind of target being as likely
+fn random_target(game: &Match, rng: &mut Rng) -> Target {
+    match rng.below(3) {
+        0 if !game.creatures.is_empty() => {
+            Target::Creature(game.creatures[rng.below(game.creatures.len())].id)
+        }
+        1 => Target::Player(rng.below(game.players.len())),
+        _ => Target::Tile(IVec2::new(
+            rng.below(game.world.width as usize) as i32,
+            rng.below(game.world.heigth as usize) as i32,
+        )),
+    }
+}
+
+/// Chances of winning of the player at the end of a playout, between 0 and 1
+fn reward(game: &Match, player: PlayerId) -> f32 {
+    match game.result {
+        Some(result) if result.winner() == player => 1.,
+        Some(_) => 0.,
+        None => 1. / (1. + (-evaluate(game, player) / SCORE_SCALE).exp()),
+    }
+}
+
+/// Replace what `player` cannot know by a guess: the hand of the opponent is dealt again
+/// from their hand and draw pile, then the draw piles are shuffled
+fn determinize(game: &mut Match, player: PlayerId, rng: &mut Rng) {
+    let opponent = &mut game.players[Match::opponent(player)];
+    let hand_size = opponent.hand.len();
+    let mut unknown: Vec<_> = opponent.hand.drain(..).collect();
+    unknown.append(&mut opponent.deck.draw_pile);
+    rng.shuffle(&mut unknown);
+    opponent.hand = unknown.split_off(unknown.len() - hand_size);
+    opponent.deck.draw_pile = unknown;
+
+    rng.shuffle(&mut game.players[player].deck.draw_pile);
+}
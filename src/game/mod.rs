@@ -1,9 +1,11 @@
+// The headless modules (rules, world, etc.) are part of the library, see `lib.rs`
+pub use cards::game::{ai, card_data, deck, effect, life, net, player, rules, tiled, world};
+
+pub mod animation;
 pub mod card;
-pub mod deck;
-pub mod effect;
 pub mod hand;
 pub mod keymapping;
-pub mod life;
-pub mod player;
+pub mod map;
+pub mod render;
 pub mod ui;
-pub mod world;
+pub mod walk;
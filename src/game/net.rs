@@ -0,0 +1,392 @@
+//! LAN matches between two instances of the game, over TCP and in lockstep.
+//!
+//! Both sides build the same match from the seed of the host and only the actions of
+//! the players go over the network, one JSON message per line. Each action comes with
+//! the hash of the state it leads to so that a desync is detected on the action causing
+//! it.
+
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    life::HasLife,
+    player::PlayerId,
+    rules::{Action, Card, Event, Match, RuleError, VictoryCondition},
+};
+
+/// Port used when the address does not give one
+pub const DEFAULT_PORT: u16 = 7878;
+/// Player of the host, the guest plays the other side
+pub const HOST_PLAYER: PlayerId = 0;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What goes over the network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// Sent by the host once the guest is connected, before each match
+    Setup(Setup),
+    /// Action of the sender, with the [`state_hash`] after it
+    Action { action: Action, hash: u64 },
+}
+
+/// Everything the guest needs to build the same match as the host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Setup {
+    /// Seed of the decks of both players
+    pub seed: u64,
+    /// Name of the map in the maps folder (ex: "map1.tmx")
+    pub map: String,
+    pub victory_conditions: Vec<VictoryCondition>,
+    /// [`state_hash`] of the new match, different assets give different matches
+    pub hash: u64,
+}
+
+/// Reason why a LAN match cannot go on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetError {
+    /// The connection could not be opened or was cut
+    Io(String),
+    /// The other side sent something that is not a message
+    InvalidMessage(String),
+    Disconnected,
+    /// The other side sent a message that does not fit at this point of the match
+    Unexpected(&'static str),
+    /// The rules refused the action of the other side
+    Refused(RuleError),
+    /// Both sides do not have the same state anymore
+    Desync {
+        turn: u32,
+        local: u64,
+        remote: u64,
+    },
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(error) => write!(f, "network error: {}", error),
+            NetError::InvalidMessage(error) => write!(f, "invalid message: {}", error),
+            NetError::Disconnected => write!(f, "the other player left"),
+            NetError::Unexpected(message) => write!(f, "unexpected message: {}", message),
+            NetError::Refused(error) => write!(f, "the other player cheated: {}", error),
+            NetError::Desync {
+                turn,
+                local,
+                remote,
+            } => write!(
+                f,
+                "desync on turn {} (state {:016x} here, {:016x} there)",
+                turn, local, remote
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+fn io_error(error: io::Error) -> NetError {
+    NetError::Io(error.to_string())
+}
+
+/// Waits for a guest, without blocking
+pub struct Host {
+    listeners: Vec<TcpListener>,
+}
+
+impl Host {
+    /// Listen on the address (ex: "0.0.0.0:7878")
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(addr).map_err(io_error)?;
+        listener.set_nonblocking(true).map_err(io_error)?;
+        Ok(Host {
+            listeners: vec![listener],
+        })
+    }
+
+    /// Listen on every IPv6 and IPv4 address of the machine
+    ///
+    /// `NOTE` on most systems the IPv6 socket accepts the IPv4 guests too and the IPv4
+    /// one cannot be opened on the same port, it is only needed on the others (Windows).
+    pub fn bind_any(port: u16) -> Result<Self, NetError> {
+        let ipv6 = Host::bind((Ipv6Addr::UNSPECIFIED, port));
+        let ipv4 = Host::bind((Ipv4Addr::UNSPECIFIED, port));
+        match (ipv6, ipv4) {
+            (Ok(mut host), Ok(ipv4)) => {
+                host.listeners.extend(ipv4.listeners);
+                Ok(host)
+            }
+            (Ok(host), Err(_)) | (Err(_), Ok(host)) => Ok(host),
+            (Err(error), Err(_)) => Err(error),
+        }
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        self.listeners[0].local_addr().map_err(io_error)
+    }
+
+    /// Return the guest if one connected since the last call
+    pub fn accept(&self) -> Result<Option<Peer>, NetError> {
+        for listener in &self.listeners {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false).map_err(io_error)?;
+                    return Peer::new(stream).map(Some);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(error) => return Err(io_error(error)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Connection to the other side
+///
+/// The messages are read on their own thread so that they can be polled every frame.
+pub struct Peer {
+    stream: TcpStream,
+    incoming: Receiver<Result<Message, NetError>>,
+}
+
+impl Peer {
+    /// Connect to a host, [`DEFAULT_PORT`] is used if the address has no port
+    ///
+    /// The address is an IP address (ex: `192.168.1.10`, `::1`, `[::1]:7878`) or a host
+    /// name (ex: `localhost`, `localhost:7878`).
+    pub fn connect(addr: &str) -> Result<Self, NetError> {
+        let addr = socket_addr(addr)?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(io_error)?;
+        Peer::new(stream)
+    }
+
+    fn new(stream: TcpStream) -> Result<Self, NetError> {
+        // The messages are small and sent one at a time
+        stream.set_nodelay(true).map_err(io_error)?;
+        let reader = BufReader::new(stream.try_clone().map_err(io_error)?);
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = line.map_err(io_error).and_then(|line| {
+                    serde_json::from_str(&line)
+                        .map_err(|error| NetError::InvalidMessage(error.to_string()))
+                });
+                let failed = message.is_err();
+                if sender.send(message).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Peer { stream, incoming })
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        let mut line = serde_json::to_string(message)
+            .map_err(|error| NetError::InvalidMessage(error.to_string()))?;
+        line.push('\n');
+        self.stream.write_all(line.as_bytes()).map_err(io_error)
+    }
+
+    /// Next message if one arrived
+    pub fn try_receive(&mut self) -> Result<Option<Message>, NetError> {
+        match self.incoming.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(NetError::Disconnected),
+        }
+    }
+
+    /// Wait for the next message
+    pub fn receive(&mut self) -> Result<Message, NetError> {
+        self.incoming
+            .recv()
+            .map_err(|_| NetError::Disconnected)
+            .and_then(|message| message)
+    }
+}
+
+/// Address to connect to, see [`Peer::connect`]
+fn socket_addr(addr: &str) -> Result<SocketAddr, NetError> {
+    if let Ok(addr) = addr.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    // The IPv6 addresses contain colons, they are parsed before looking for a port
+    if let Ok(ip) = addr.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, DEFAULT_PORT));
+    }
+    let with_port = if addr.contains(':') {
+        addr.to_string()
+    } else {
+        format!("{}:{}", addr, DEFAULT_PORT)
+    };
+    with_port
+        .to_socket_addrs()
+        .map_err(io_error)?
+        .next()
+        .ok_or_else(|| NetError::Io(format!("unknown address {}", with_port)))
+}
+
+/// Keeps both sides of a match in sync: the actions of the local player are sent and
+/// the actions of the other player are applied in the same order
+pub struct Lockstep {
+    peer: Peer,
+    pub local_player: PlayerId,
+}
+
+impl Lockstep {
+    pub fn new(peer: Peer, local_player: PlayerId) -> Self {
+        Lockstep { peer, local_player }
+    }
+
+    /// Send an action of the local player, already applied on `game`
+    pub fn send(&mut self, action: Action, game: &Match) -> Result<(), NetError> {
+        let hash = state_hash(game);
+        self.peer.send(&Message::Action { action, hash })
+    }
+
+    /// Apply the next action of the other player if it arrived
+    pub fn poll(&mut self, game: &mut Match) -> Result<Option<Vec<Event>>, NetError> {
+        match self.peer.try_receive()? {
+            Some(message) => self.apply(message, game).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Wait for the next action of the other player and apply it
+    pub fn wait(&mut self, game: &mut Match) -> Result<Vec<Event>, NetError> {
+        let message = self.peer.receive()?;
+        self.apply(message, game)
+    }
+
+    fn apply(&self, message: Message, game: &mut Match) -> Result<Vec<Event>, NetError> {
+        let Message::Action { action, hash } = message else {
+            return Err(NetError::Unexpected("setup during a match"));
+        };
+        if game.turn.active_player == self.local_player {
+            return Err(NetError::Unexpected(
+                "action during the turn of the other side",
+            ));
+        }
+        let events = game.apply(action).map_err(NetError::Refused)?;
+        check_sync(game, hash)?;
+        Ok(events)
+    }
+}
+
+/// Compare the state with the hash of the other side
+pub fn check_sync(game: &Match, remote: u64) -> Result<(), NetError> {
+    let local = state_hash(game);
+    if local == remote {
+        Ok(())
+    } else {
+        Err(NetError::Desync {
+            turn: game.turn.number,
+            local,
+            remote,
+        })
+    }
+}
+
+/// Hash of what the players can see and do in the match, the same on every platform
+///
+/// NOTE the hash is not cryptographic, it only detects the bugs breaking the lockstep.
+pub fn state_hash(game: &Match) -> u64 {
+    let mut hash = Fnv::default();
+    hash.u64(game.turn.number as u64);
+    hash.u64(game.turn.active_player as u64);
+    hash.u64(game.turn.phase as u64);
+
+    for player in &game.players {
+        hash.u64(player.get_life_current() as u64);
+        hash.u64(player.get_ap_current() as u64);
+        hash.u64(player.get_ap_max() as u64);
+        for cards in [
+            &player.hand,
+            &player.deck.draw_pile,
+            &player.deck.discard_pile,
+            &player.deck.exile,
+        ] {
+            hash.cards(cards);
+        }
+    }
+
+    hash.u64(game.creatures.len() as u64);
+    for creature in &game.creatures {
+        hash.u64(creature.id as u64);
+        hash.u64(creature.owner as u64);
+        hash.str(&creature.card.basic_info.name);
+        hash.u64(creature.hp_current as u64);
+        hash.u64(creature.pos.x as u64);
+        hash.u64(creature.pos.y as u64);
+        hash.u64(creature.movement_left as u64);
+        hash.u64(creature.summoning_sick as u64);
+        hash.u64(creature.has_attacked as u64);
+    }
+
+    if let Some(result) = game.result {
+        hash.u64(result.loser as u64);
+        hash.str(&result.condition.to_string());
+    }
+    hash.0
+}
+
+/// 64 bits FNV-1a, unlike the hasher of the standard library it is the same on every
+/// platform and version of Rust
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    /// The length is hashed first so that ("ab", "c") and ("a", "bc") differ
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+
+    fn cards(&mut self, cards: &[Card]) {
+        self.u64(cards.len() as u64);
+        for card in cards {
+            self.str(card.get_name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv4_ipv6_and_host_names_are_parsed() {
+        let addr = |addr: &str| socket_addr(addr).map(|addr| addr.to_string());
+        assert_eq!(addr("127.0.0.1"), Ok("127.0.0.1:7878".to_string()));
+        assert_eq!(addr("127.0.0.1:9000"), Ok("127.0.0.1:9000".to_string()));
+        assert_eq!(addr("::1"), Ok("[::1]:7878".to_string()));
+        assert_eq!(addr("[::1]:9000"), Ok("[::1]:9000".to_string()));
+        assert_eq!(
+            addr("localhost:9000").map(|a| a.ends_with(":9000")),
+            Ok(true)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use glam::IVec2;
+
+use super::world::World;
+
+/// Tiles reachable from a position within a movement budget, with the cheapest
+/// way to reach each of them
+pub struct Reachable {
+    from: IVec2,
+    /// Cost to reach each tile and the tile it is reached from
+    tiles: HashMap<IVec2, (u32, IVec2)>,
+}
+
+impl Reachable {
+    /// Search the cheapest paths from `from`, walking onto a tile costs its movement cost
+    ///
+    /// Tiles that cannot be walked on and those for which `is_blocked` is true cannot
+    /// be walked on nor through.
+    pub fn search(
+        world: &World,
+        from: IVec2,
+        budget: u32,
+        is_blocked: impl Fn(IVec2) -> bool,
+    ) -> Self {
+        let mut tiles = HashMap::from([(from, (0, from))]);
+        // IVec2 is not ordered, the positions are stored as tuples
+        let mut queue = BinaryHeap::from([Reverse((0, from.x, from.y))]);
+
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let pos = IVec2::new(x, y);
+            if cost > tiles[&pos].0 {
+                // A cheaper path to the tile was already found
+                continue;
+            }
+            for next in world.neighbors(pos) {
+                let Some(tile) = world.tile_at(next) else {
+                    continue;
+                };
+                if !tile.properties.walkable || is_blocked(next) {
+                    continue;
+                }
+                let next_cost = cost + tile.properties.movement_cost;
+                if next_cost > budget || tiles.get(&next).is_some_and(|(c, _)| *c <= next_cost) {
+                    continue;
+                }
+                tiles.insert(next, (next_cost, pos));
+                queue.push(Reverse((next_cost, next.x, next.y)));
+            }
+        }
+
+        Reachable { from, tiles }
+    }
+
+    /// Tiles that can be reached, the starting one excluded, row by row
+    ///
+    /// The order does not depend on the hash map so that the AI stays deterministic.
+    pub fn tiles(&self) -> impl Iterator<Item = IVec2> + '_ {
+        let mut tiles: Vec<IVec2> = self
+            .tiles
+            .keys()
+            .copied()
+            .filter(|pos| *pos != self.from)
+            .collect();
+        tiles.sort_by_key(|pos| (pos.y, pos.x));
+        tiles.into_iter()
+    }
+
+    /// Movement needed to reach the tile
+    pub fn cost(&self, to: IVec2) -> Option<u32> {
+        self.tiles.get(&to).map(|(cost, _)| *cost)
+    }
+
+    /// Cheapest path to the tile, from the starting tile to `to` both included
+    pub fn path(&self, to: IVec2) -> Option<Vec<IVec2>> {
+        let mut pos = to;
+        let mut path = vec![pos];
+        while pos != self.from {
+            pos = self.tiles.get(&pos)?.1;
+            path.push(pos);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::ivec2;
+
+    use super::*;
+    use crate::game::tiled::TiledMap;
+
+    #[test]
+    fn cheapest_paths_within_the_budget() {
+        let world = World::new(6, 6);
+        let reachable = Reachable::search(&world, ivec2(0, 0), 3, |_| false);
+        assert_eq!(reachable.cost(ivec2(0, 0)), Some(0));
+        assert_eq!(reachable.cost(ivec2(2, 1)), Some(3));
+        assert_eq!(reachable.cost(ivec2(3, 1)), None);
+        assert_eq!(reachable.tiles().count(), 9);
+        assert_eq!(
+            reachable.path(ivec2(0, 2)),
+            Some(vec![ivec2(0, 0), ivec2(0, 1), ivec2(0, 2)])
+        );
+        assert_eq!(reachable.path(ivec2(3, 2)), None);
+    }
+
+    #[test]
+    fn blocked_tiles_are_walked_around() {
+        // A wall on the middle column, except on the last row
+        let world = World::new(3, 3);
+        let reachable = Reachable::search(&world, ivec2(0, 0), 10, |pos| pos.x == 1 && pos.y < 2);
+        assert_eq!(reachable.cost(ivec2(1, 0)), None);
+        assert_eq!(reachable.cost(ivec2(2, 0)), Some(6));
+        assert_eq!(
+            reachable.path(ivec2(2, 0)),
+            Some(vec![
+                ivec2(0, 0),
+                ivec2(0, 1),
+                ivec2(0, 2),
+                ivec2(1, 2),
+                ivec2(2, 2),
+                ivec2(2, 1),
+                ivec2(2, 0),
+            ])
+        );
+    }
+
+    #[test]
+    fn movement_cost_of_the_tiles() {
+        // Mud in the middle of the first row, a rock at the end of the second one
+        let json = r#"{
+            "width": 3, "height": 2, "tilewidth": 32, "tileheight": 32,
+            "layers": [{"name": "ground", "type": "tilelayer", "data": [1, 2, 1, 1, 1, 3]}],
+            "tilesets": [{"firstgid": 1, "tiles": [
+                {"id": 1, "properties": [{"name": "movement_cost", "type": "int", "value": 4}]},
+                {"id": 2, "properties": [{"name": "walkable", "type": "bool", "value": false}]}
+            ]}]
+        }"#;
+        let world = World::from_tiled(&TiledMap::from_json(json).unwrap()).unwrap();
+        let reachable = Reachable::search(&world, ivec2(0, 0), 10, |_| false);
+        assert_eq!(reachable.cost(ivec2(1, 0)), Some(4));
+        assert_eq!(reachable.cost(ivec2(2, 1)), None);
+        // Around the mud would go through the rock
+        assert_eq!(reachable.cost(ivec2(2, 0)), Some(5));
+        assert_eq!(
+            reachable.path(ivec2(2, 0)),
+            Some(vec![ivec2(0, 0), ivec2(1, 0), ivec2(2, 0)])
+        );
+    }
+}
@@ -1,36 +1,72 @@
-use super::{deck::Deck, life::HasLife};
+use super::{deck::Deck, life::HasLife, rules::Card};
+
+/// Index of a player in a match
+pub type PlayerId = usize;
 
 /**
 NOTE:
  - On devrait probablement pas toujours garder tous les decks du player pour ne pas que pendant qu'il joue une partie, il garde tout ses decks en mémoires.
  -
  **/
+#[allow(dead_code)]
+#[derive(Clone)]
 pub struct Player {
-    id_player: u32,   // unique identifier
-    name: String,     // in game name
-    decks: Vec<Deck>, // all of the decks of a player
-    // deck_current: Deck, // the current deck of a player
-    hp_max: u32,     // maximum health of a player
-    hp_current: u32, // health during game
-    ap_max: u32,     // action points at start of game
-    ap_current: u32, // action points during game
+    id_player: u32,      // unique identifier
+    name: String,        // in game name
+    decks: Vec<Deck>,    // all of the decks of a player
+    pub deck: Deck,      // the current deck of a player
+    hp_max: u32,         // maximum health of a player
+    hp_current: u32,     // health during game
+    ap_max: u32,         // action points given at the start of each turn, grows every turn
+    ap_current: u32,     // action points left during the turn
+    pub hand: Vec<Card>, // cards currently in the hand of the player
 }
 
 impl Player {
-    fn new(id_player: u32, name: String) -> Self {
+    pub fn new(id_player: u32, name: String) -> Self {
         // NOTE: les hp et ap vont possiblement varier selon le mode de jeux. Possiblement les add en paramètres ou ajouter un autre constructeur plus tard
         Player {
             id_player,
             name,
             // ADD DECK Constructor
             decks: vec![],
-            // deck_current: Deck {},
+            deck: Deck::new(0, vec![], 0),
             hp_max: 100,
             hp_current: 100,
-            ap_max: 10,
-            ap_current: 10,
+            ap_max: 0,
+            ap_current: 0,
+            hand: vec![],
         }
     }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_ap_current(&self) -> u32 {
+        self.ap_current
+    }
+
+    pub fn get_ap_max(&self) -> u32 {
+        self.ap_max
+    }
+
+    /// Return if the player has enough action points to pay for the card
+    pub fn can_afford(&self, card: &Card) -> bool {
+        card.get_basic_info().cost <= self.ap_current
+    }
+
+    /// Spend action points, the player must be able to afford them
+    pub fn spend_ap(&mut self, cost: u32) {
+        self.ap_current -= cost;
+    }
+
+    /// Increase the maximum of action points by `ramp` without exceeding `cap`,
+    /// then refill the action points of the player
+    pub fn refill_ap(&mut self, ramp: u32, cap: u32) {
+        self.ap_max = cap.min(self.ap_max + ramp);
+        self.ap_current = self.ap_max;
+    }
 }
 
 impl HasLife for Player {
@@ -50,3 +86,22 @@ impl HasLife for Player {
         self.hp_current = delta;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_points_ramp_up_to_the_cap() {
+        let mut player = Player::new(0, "Alice".to_string());
+        player.refill_ap(1, 2);
+        assert_eq!((player.get_ap_current(), player.get_ap_max()), (1, 1));
+
+        player.spend_ap(1);
+        assert_eq!(player.get_ap_current(), 0);
+
+        player.refill_ap(1, 2);
+        player.refill_ap(1, 2);
+        assert_eq!((player.get_ap_current(), player.get_ap_max()), (2, 2));
+    }
+}
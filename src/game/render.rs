@@ -0,0 +1,122 @@
+use macroquad::{
+    color::Color,
+    math::Vec2,
+    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+};
+
+use super::world::world_depth;
+
+/// What is drawn at a given depth, from the first drawn to the last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    /// Tiles and objects of the layer of the map at this index
+    Map(usize),
+    Creature,
+}
+
+/// Texture waiting to be drawn by a [`RenderQueue`]
+struct DrawItem<'a> {
+    depth: f32,
+    layer: Layer,
+    texture: &'a Texture2D,
+    pos: Vec2,
+    color: Color,
+    params: DrawTextureParams,
+}
+
+/// Textures of the map drawn back to front so that what is in front of the view hides
+/// what is behind it
+///
+/// The textures are sorted by isometric depth (map x + y), then by [`Layer`], then in
+/// the order in which they were pushed.
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    items: Vec<DrawItem<'a>>,
+}
+
+impl<'a> RenderQueue<'a> {
+    /// Draw `texture` at `pos`, its depth is the one of `anchor`
+    ///
+    /// * `anchor` : world position of the tile on which the texture stands
+    pub fn push(
+        &mut self,
+        anchor: Vec2,
+        layer: Layer,
+        texture: &'a Texture2D,
+        pos: Vec2,
+        color: Color,
+        params: DrawTextureParams,
+    ) {
+        self.items.push(DrawItem {
+            depth: world_depth(anchor),
+            layer,
+            texture,
+            pos,
+            color,
+            params,
+        });
+    }
+
+    /// Put the textures in the order they are drawn
+    fn sort(&mut self) {
+        // Stable sort, to keep the push order at equal depth and layer
+        self.items
+            .sort_by(|a, b| a.depth.total_cmp(&b.depth).then(a.layer.cmp(&b.layer)));
+    }
+
+    /// Draw and remove every texture of the queue
+    pub fn draw(&mut self) {
+        self.sort();
+        for item in self.items.drain(..) {
+            draw_texture_ex(
+                item.texture,
+                item.pos.x,
+                item.pos.y,
+                item.color,
+                item.params,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::{
+        color::WHITE,
+        math::{ivec2, vec2},
+        miniquad::{RawId, TextureId},
+    };
+
+    use super::*;
+    use crate::game::world::map_to_world;
+
+    #[test]
+    fn textures_are_drawn_back_to_front() {
+        // Never drawn, the tests have no window
+        let texture = Texture2D::from_miniquad_texture(TextureId::from_raw_id(RawId::OpenGl(0)));
+        let mut queue = RenderQueue::default();
+        let mut push = |tile, layer, id: f32| {
+            let params = DrawTextureParams::default();
+            queue.push(
+                map_to_world(tile),
+                layer,
+                &texture,
+                vec2(id, 0.),
+                WHITE,
+                params,
+            );
+        };
+        push(ivec2(1, 1), Layer::Creature, 0.);
+        push(ivec2(1, 1), Layer::Map(1), 1.);
+        push(ivec2(2, 0), Layer::Map(0), 2.);
+        push(ivec2(0, 0), Layer::Map(1), 3.);
+        push(ivec2(0, 1), Layer::Map(0), 4.);
+        push(ivec2(0, 0), Layer::Map(0), 5.);
+
+        queue.sort();
+        let order: Vec<f32> = queue.items.iter().map(|item| item.pos.x).collect();
+        // Depth 0, then 1, then 2 in the order of the layers. The tiles of equal depth
+        // and layer stay in the order they were pushed.
+        assert_eq!(order, [5., 3., 4., 2., 1., 0.]);
+    }
+}
@@ -0,0 +1,35 @@
+use glam::IVec2;
+use serde::{Deserialize, Serialize};
+
+use super::creature::CreatureId;
+use crate::game::effect::Target;
+
+/// Everything a player can do during a match.
+///
+/// Both the user inputs and the tools (AI, simulations, etc.) go through those
+/// actions so that they are validated by the same rules. They are also what goes over
+/// the network in LAN matches, see [`crate::game::net`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Play the card at the index `card` of the hand on `targets`
+    ///
+    /// Creatures are summoned on a tile while effects have their own targets,
+    /// see [`crate::game::effect::Effect::nb_targets`].
+    PlayCard { card: usize, targets: Vec<Target> },
+    /// Walk a creature of the active player to the tile `to` along the shortest path
+    ///
+    /// Each step uses one point of the movement of the creature for this turn.
+    MoveCreature { creature: CreatureId, to: IVec2 },
+    /// Attack an enemy creature or the enemy player with a creature of the active player
+    ///
+    /// Only possible during the combat phase, once per turn and not on the turn the
+    /// creature was summoned.
+    Attack {
+        attacker: CreatureId,
+        target: Target,
+    },
+    /// Go to the next phase of the turn (ex: from the main phase to the combat phase)
+    NextPhase,
+    /// End the turn of the active player, the opponent becomes the active player
+    EndTurn,
+}
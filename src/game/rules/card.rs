@@ -0,0 +1,114 @@
+use glam::Vec4;
+
+use crate::game::effect::Effect;
+
+/// Card as known by the rules, it does not hold anything related to rendering
+/// except for the references to its assets.
+#[derive(Clone)]
+pub enum Card {
+    Creature(CreatureCard),
+    Effect(EffectCard),
+}
+
+#[derive(Clone)]
+pub struct CardBasicInfo {
+    pub name: String, // name of the card
+    // image_front: Path,   // Path to the image on the front of the card
+    // image_back: Path,    // Path to the image on the back of the card
+    pub description: String, // description of the card
+    pub cost: u32,           // cost of the card
+    // pub counter: Option<u32>, // counter before card take effects
+    /// Background color of the card (r, g, b, a)
+    pub card_color: Vec4,
+}
+
+/// Creature Card
+#[derive(Clone)]
+pub struct CreatureCard {
+    pub basic_info: CardBasicInfo,
+    pub hp_max: u32,
+    /// Damage dealt when attacking or retaliating
+    pub attack: u32,
+    /// Maximum distance at which the creature can attack
+    pub range: u32,
+    /// Total movement the creature can do
+    pub movement: u32,
+    /// Path of the creature's assets without the frame suffix (ex: "creatures/goblin/goblin")
+    pub img_path: String,
+    /// Effect triggered where the creature dies, see [`super::Match`] for how it is targeted
+    pub on_death: Option<Effect>,
+}
+
+impl CreatureCard {
+    pub fn new(
+        basic_info: CardBasicInfo,
+        hp_max: u32,
+        attack: u32,
+        range: u32,
+        movement: u32,
+        img_path: &str,
+    ) -> Self {
+        CreatureCard {
+            basic_info,
+            hp_max,
+            attack,
+            range,
+            movement,
+            img_path: img_path.to_string(),
+            on_death: None,
+        }
+    }
+
+    pub fn with_on_death(mut self, effect: Effect) -> Self {
+        self.on_death = Some(effect);
+        self
+    }
+}
+
+/// Card with effect
+#[derive(Clone)]
+pub struct EffectCard {
+    pub basic_info: CardBasicInfo,
+    pub effect: Effect,
+}
+
+impl EffectCard {
+    pub fn new(basic_info: CardBasicInfo, effect: Effect) -> Self {
+        EffectCard { basic_info, effect }
+    }
+}
+
+impl Card {
+    pub fn get_name(&self) -> &str {
+        &self.get_basic_info().name
+    }
+
+    /// Number of targets to choose to play the card
+    pub fn nb_targets(&self) -> usize {
+        match self {
+            Card::Creature(_) => 1,
+            Card::Effect(effect_card) => effect_card.effect.nb_targets(),
+        }
+    }
+
+    /// `img_path` of every creature the card can put on the map, its tokens included
+    pub fn creature_img_paths(&self) -> Vec<&str> {
+        let (creature, effect) = match self {
+            Card::Creature(creature) => (Some(creature), creature.on_death.as_ref()),
+            Card::Effect(effect_card) => (None, Some(&effect_card.effect)),
+        };
+        let token = effect.and_then(|effect| effect.token.as_deref());
+        creature
+            .into_iter()
+            .chain(token)
+            .map(|creature| creature.img_path.as_str())
+            .collect()
+    }
+
+    pub fn get_basic_info(&self) -> &CardBasicInfo {
+        match self {
+            Card::Creature(creature) => &creature.basic_info,
+            Card::Effect(effect) => &effect.basic_info,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use glam::IVec2;
+
+use super::card::CreatureCard;
+use crate::game::{life::HasLife, player::PlayerId, world::distance};
+
+/// Unique identifier of a creature on the board
+pub type CreatureId = u32;
+
+/// Creature summoned on the board
+#[derive(Clone)]
+pub struct Creature {
+    pub id: CreatureId,
+    /// Card the creature was summoned from
+    pub card: CreatureCard,
+    /// Player controlling the creature
+    pub owner: PlayerId,
+    pub hp_current: u32,
+    /// Position of the creature on the map
+    pub pos: IVec2,
+    /// Movement the creature can still do this turn
+    pub movement_left: u32,
+    /// Creatures cannot attack during the turn they are summoned
+    pub summoning_sick: bool,
+    /// Creatures can only attack once per turn
+    pub has_attacked: bool,
+    /// Tokens are not real cards, they vanish instead of going to the discard pile
+    pub is_token: bool,
+}
+
+impl Creature {
+    pub fn new(id: CreatureId, card: CreatureCard, owner: PlayerId, pos: IVec2) -> Self {
+        Creature {
+            id,
+            hp_current: card.hp_max,
+            movement_left: card.movement,
+            summoning_sick: true,
+            has_attacked: false,
+            card,
+            owner,
+            pos,
+            is_token: false,
+        }
+    }
+}
+
+impl Creature {
+    /// Return if the creature can attack this turn
+    pub fn can_attack(&self) -> bool {
+        self.is_alive() && !self.summoning_sick && !self.has_attacked
+    }
+
+    /// Return if something at the given position is within the attack range of the creature
+    pub fn in_range(&self, pos: IVec2) -> bool {
+        distance(self.pos, pos) <= self.card.range
+    }
+}
+
+impl HasLife for Creature {
+    fn is_alive(&self) -> bool {
+        self.hp_current > 0
+    }
+
+    fn get_life_current(&self) -> u32 {
+        self.hp_current
+    }
+
+    fn get_life_max(&self) -> u32 {
+        self.card.hp_max
+    }
+
+    fn set_life(&mut self, delta: u32) {
+        self.hp_current = delta;
+    }
+}
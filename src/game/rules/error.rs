@@ -0,0 +1,82 @@
+use std::fmt;
+
+use glam::IVec2;
+
+use super::{creature::CreatureId, turn::Phase};
+use crate::game::effect::Target;
+
+/// Reason why an action was refused by the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// No card at the given index in hand
+    NoSuchCard(usize),
+    /// The given map position is not part of the map
+    OutOfMap(IVec2),
+    /// The action cannot be done during the current phase
+    WrongPhase(Phase),
+    /// The card cannot be played on this target
+    InvalidTarget(Target),
+    /// The target is on the side of the active player and friendly fire is disabled
+    FriendlyFire(Target),
+    /// Not every target of the card has been chosen
+    MissingTargets { needed: usize, given: usize },
+    /// A creature already stands on the tile
+    Occupied(IVec2),
+    /// Creatures cannot stand on the tile
+    NotWalkable(IVec2),
+    /// The tile is outside of the deployment zones of the player
+    OutsideDeploymentZone(IVec2),
+    /// The tile is too far away
+    TooFar(IVec2),
+    /// No path within the movement left of the creature leads to the tile
+    Unreachable(IVec2),
+    /// The creature does not exist or is not controlled by the active player
+    NotYourCreature(CreatureId),
+    /// The creature was summoned this turn and cannot attack yet
+    SummoningSick(CreatureId),
+    /// The creature already attacked this turn
+    AlreadyAttacked(CreatureId),
+    /// The active player does not have enough action points to play the card
+    NotEnoughAp { cost: u32, available: u32 },
+    /// The match already has a winner
+    MatchOver,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::NoSuchCard(index) => write!(f, "no card at index {} in hand", index),
+            RuleError::OutOfMap(pos) => write!(f, "position {} is outside of the map", pos),
+            RuleError::WrongPhase(phase) => write!(f, "cannot be done during the {} phase", phase),
+            RuleError::InvalidTarget(target) => write!(f, "cannot be played on {}", target),
+            RuleError::FriendlyFire(target) => {
+                write!(f, "{} is on your side, friendly fire is disabled", target)
+            }
+            RuleError::MissingTargets { needed, given } => {
+                write!(f, "needs {} targets but {} were given", needed, given)
+            }
+            RuleError::Occupied(pos) => write!(f, "tile {} is already occupied", pos),
+            RuleError::NotWalkable(pos) => write!(f, "tile {} cannot be walked on", pos),
+            RuleError::OutsideDeploymentZone(pos) => {
+                write!(f, "tile {} is outside of your deployment zone", pos)
+            }
+            RuleError::TooFar(pos) => write!(f, "tile {} is too far away", pos),
+            RuleError::Unreachable(pos) => write!(f, "tile {} cannot be reached", pos),
+            RuleError::NotYourCreature(id) => write!(f, "creature #{} is not yours", id),
+            RuleError::SummoningSick(id) => {
+                write!(f, "creature #{} cannot attack the turn it is summoned", id)
+            }
+            RuleError::AlreadyAttacked(id) => {
+                write!(f, "creature #{} already attacked this turn", id)
+            }
+            RuleError::NotEnoughAp { cost, available } => write!(
+                f,
+                "costs {} action points but only {} are available",
+                cost, available
+            ),
+            RuleError::MatchOver => write!(f, "the match is over"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
@@ -0,0 +1,66 @@
+use glam::IVec2;
+
+use super::{creature::CreatureId, victory::VictoryCondition};
+use crate::game::{effect::Target, life::DamageOutcome, player::PlayerId};
+
+/// Something that happened during a match
+///
+/// Events are returned by [`super::Match::apply`] so that the renderer (animations,
+/// sounds, etc.) and the tools can react to what the action caused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    CreatureSummoned {
+        id: CreatureId,
+        owner: PlayerId,
+        pos: IVec2,
+    },
+    /// The creature walked along the path, from its previous position to the new one
+    CreatureWalked {
+        id: CreatureId,
+        path: Vec<IVec2>,
+    },
+    /// The creature was moved directly to the new position
+    CreatureMoved {
+        id: CreatureId,
+        from: IVec2,
+        to: IVec2,
+    },
+    /// The creature attacked the target, the damage follows as separate events
+    CreatureAttacked {
+        attacker: CreatureId,
+        target: Target,
+    },
+    /// The attacked creature struck back
+    CreatureRetaliated {
+        defender: CreatureId,
+        attacker: CreatureId,
+    },
+    CreatureDamaged {
+        id: CreatureId,
+        outcome: DamageOutcome,
+    },
+    CreatureHealed {
+        id: CreatureId,
+        amount: u32,
+    },
+    /// The creature was removed from the board
+    CreatureDied {
+        id: CreatureId,
+        owner: PlayerId,
+        pos: IVec2,
+    },
+    PlayerDamaged {
+        player: PlayerId,
+        outcome: DamageOutcome,
+    },
+    PlayerHealed {
+        player: PlayerId,
+        amount: u32,
+    },
+    /// The player lost the match because the opponent fulfilled the condition, see
+    /// [`super::Match::result`]
+    PlayerDefeated {
+        player: PlayerId,
+        condition: VictoryCondition,
+    },
+}
@@ -0,0 +1,23 @@
+//! Headless rules of the game: state, actions and their validation.
+//!
+//! This module must stay free of any rendering or input code (macroquad) so it
+//! can be reused by tests, simulations, servers and AI.
+
+pub mod action;
+pub mod card;
+pub mod creature;
+pub mod error;
+pub mod event;
+pub mod rng;
+pub mod state;
+pub mod turn;
+pub mod victory;
+
+pub use action::Action;
+pub use card::{Card, CardBasicInfo, CreatureCard, EffectCard};
+pub use creature::{Creature, CreatureId};
+pub use error::RuleError;
+pub use event::Event;
+pub use state::Match;
+pub use turn::{Phase, Turn};
+pub use victory::{MatchResult, MatchStats, VictoryCondition};
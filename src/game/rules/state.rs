@@ -0,0 +1,1201 @@
+use glam::IVec2;
+
+use super::{
+    action::Action,
+    card::{Card, CreatureCard},
+    creature::{Creature, CreatureId},
+    error::RuleError,
+    event::Event,
+    turn::{Phase, Turn},
+    victory::{MatchResult, MatchStats, VictoryCondition},
+};
+use crate::game::{
+    deck::Draw,
+    effect::{Effect, EffectType, Target, TargettedCardEffect},
+    life::{HasLife, LifeChange},
+    pathfinding::Reachable,
+    player::{Player, PlayerId},
+    world::{distance, World},
+};
+
+/// Number of cards drawn by each player when the match starts
+pub const OPENING_HAND_SIZE: usize = 3;
+/// Number of cards drawn during the draw phase
+pub const CARDS_DRAWN_PER_TURN: usize = 1;
+/// Action points added to the maximum of the active player at the start of each of their turn
+pub const AP_RAMP_PER_TURN: u32 = 1;
+/// Maximum of action points a player can have
+pub const AP_CAP: u32 = 10;
+/// Maximum of target combinations listed for a card by [`Match::legal_actions`]
+pub const MAX_TARGET_COMBINATIONS: usize = 64;
+
+/// Complete state of a match
+///
+/// The state is only modified through [`Match::apply`] which validates the
+/// actions before executing them. The renderer only reads it.
+#[derive(Clone)]
+pub struct Match {
+    pub world: World,
+    /// Both sides of the match, indexed by [`PlayerId`]
+    pub players: [Player; 2],
+    pub turn: Turn,
+    /// Creatures currently on the board
+    pub creatures: Vec<Creature>,
+    /// How the match ended, if it is over
+    pub result: Option<MatchResult>,
+    /// Ways to win the match, checked after each action
+    pub victory_conditions: Vec<VictoryCondition>,
+    pub stats: MatchStats,
+    /// If heal and damage effects can affect both sides. Otherwise the damage only hits
+    /// the opponent and their creatures, and the heal only the player and their creatures.
+    /// Creatures never attack their own side.
+    pub friendly_fire: bool,
+    /// Id given to the next summoned creature
+    next_creature_id: CreatureId,
+    /// Events caused by the action being applied
+    events: Vec<Event>,
+}
+
+impl Match {
+    /// Create a match, draw the opening hands and start the turn of the first player
+    ///
+    /// The decks of the players are expected to be already shuffled.
+    pub fn new(world: World, players: [Player; 2]) -> Self {
+        let mut new_match = Match {
+            world,
+            players,
+            turn: Turn::new(0),
+            creatures: Vec::new(),
+            result: None,
+            victory_conditions: VictoryCondition::DEFAULT.to_vec(),
+            stats: MatchStats::default(),
+            friendly_fire: false,
+            next_creature_id: 0,
+            events: Vec::new(),
+        };
+        for player in 0..new_match.players.len() {
+            new_match.draw(player, OPENING_HAND_SIZE);
+        }
+        new_match.start_turn(0);
+        new_match.events.clear();
+        new_match
+    }
+
+    /// Player whose turn it is
+    pub fn active_player(&self) -> &Player {
+        &self.players[self.turn.active_player]
+    }
+
+    /// Return the id of the opponent of the given player
+    pub fn opponent(player: PlayerId) -> PlayerId {
+        1 - player
+    }
+
+    /// Return if a player lost the match
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Return the creature standing at the given map position
+    pub fn creature_at(&self, pos: IVec2) -> Option<&Creature> {
+        self.creature(self.world.occupant(pos)?)
+    }
+
+    pub fn creature(&self, id: CreatureId) -> Option<&Creature> {
+        self.creatures.iter().find(|creature| creature.id == id)
+    }
+
+    /// Tile standing for the player on the map closest to `from`, the creatures attack
+    /// the player from there
+    ///
+    /// It is one of their spawn points, or a tile of their deployment zones on maps
+    /// without spawn point for them. `None` if the map has neither.
+    pub fn closest_player_tile(&self, player: PlayerId, from: IVec2) -> Option<IVec2> {
+        let spawn_points: Vec<IVec2> = self
+            .world
+            .spawn_points
+            .iter()
+            .filter(|spawn| spawn.player == player)
+            .map(|spawn| spawn.pos)
+            .collect();
+        let tiles = if spawn_points.is_empty() {
+            self.world
+                .deployment_zones
+                .iter()
+                .filter(|zone| zone.player == player)
+                .map(|zone| from.clamp(zone.min, zone.max))
+                .collect()
+        } else {
+            spawn_points
+        };
+        tiles.into_iter().min_by_key(|pos| distance(from, *pos))
+    }
+
+    /// Tiles the creature can walk to with the movement it has left this turn
+    ///
+    /// Creatures cannot walk through each other.
+    pub fn reachable(&self, id: CreatureId) -> Option<Reachable> {
+        let creature = self.creature(id)?;
+        Some(Reachable::search(
+            &self.world,
+            creature.pos,
+            creature.movement_left,
+            |pos| self.creature_at(pos).is_some(),
+        ))
+    }
+
+    /// Every target the creature can attack right now
+    pub fn attack_targets(&self, attacker: CreatureId) -> Vec<Target> {
+        let creatures = self
+            .creatures
+            .iter()
+            .map(|creature| Target::Creature(creature.id));
+        let players = (0..self.players.len()).map(Target::Player);
+
+        creatures
+            .chain(players)
+            .filter(|target| {
+                let action = Action::Attack {
+                    attacker,
+                    target: *target,
+                };
+                self.validate(&action).is_ok()
+            })
+            .collect()
+    }
+
+    /// Every action the active player can do right now
+    ///
+    /// The target combinations of each card are cut at [`MAX_TARGET_COMBINATIONS`], the
+    /// cards with several targets would have too many of them.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        if self.is_over() || !self.turn.phase.is_interactive() {
+            return Vec::new();
+        }
+
+        let player = self.turn.active_player;
+        let own_creatures = self
+            .creatures
+            .iter()
+            .filter(|creature| creature.owner == player);
+        let mut actions = Vec::new();
+        match self.turn.phase {
+            Phase::Main => {
+                for (card, in_hand) in self.active_player().hand.iter().enumerate() {
+                    let mut combinations = vec![Vec::new()];
+                    for _ in 0..in_hand.nb_targets() {
+                        combinations = combinations
+                            .iter()
+                            .flat_map(|chosen| {
+                                self.valid_targets(card, chosen)
+                                    .into_iter()
+                                    .map(move |target| [chosen.clone(), vec![target]].concat())
+                            })
+                            .take(MAX_TARGET_COMBINATIONS)
+                            .collect();
+                    }
+                    actions.extend(
+                        combinations
+                            .into_iter()
+                            .map(|targets| Action::PlayCard { card, targets }),
+                    );
+                }
+                for creature in own_creatures {
+                    if let Some(reachable) = self.reachable(creature.id) {
+                        actions.extend(reachable.tiles().map(|to| Action::MoveCreature {
+                            creature: creature.id,
+                            to,
+                        }));
+                    }
+                }
+            }
+            Phase::Combat => {
+                for creature in own_creatures {
+                    actions.extend(self.attack_targets(creature.id).into_iter().map(|target| {
+                        Action::Attack {
+                            attacker: creature.id,
+                            target,
+                        }
+                    }));
+                }
+            }
+            _ => {}
+        }
+        actions.push(Action::NextPhase);
+        actions.push(Action::EndTurn);
+
+        actions.retain(|action| self.validate(action).is_ok());
+        actions
+    }
+
+    fn creature_mut(&mut self, id: CreatureId) -> Option<&mut Creature> {
+        self.creatures.iter_mut().find(|creature| creature.id == id)
+    }
+
+    /// Return every target that can be chosen next for the card at the index `card`
+    /// of the active player's hand, knowing the targets already `chosen`
+    pub fn valid_targets(&self, card: usize, chosen: &[Target]) -> Vec<Target> {
+        let tiles = (0..self.world.heigth as i32).flat_map(|y| {
+            (0..self.world.width as i32).map(move |x| Target::Tile(IVec2::new(x, y)))
+        });
+        let creatures = self
+            .creatures
+            .iter()
+            .map(|creature| Target::Creature(creature.id));
+        let players = (0..self.players.len()).map(Target::Player);
+
+        tiles
+            .chain(creatures)
+            .chain(players)
+            .filter(|target| {
+                let mut targets = chosen.to_vec();
+                targets.push(*target);
+                self.check_targets(card, &targets).is_ok()
+            })
+            .collect()
+    }
+
+    /// Check if the card at the index `card` of the active player's hand can be played
+    /// on the given targets, even if not all of them have been chosen yet
+    pub fn check_targets(&self, card: usize, targets: &[Target]) -> Result<(), RuleError> {
+        self.expect_phase(Phase::Main)?;
+        let player = self.active_player();
+        let Some(card) = player.hand.get(card) else {
+            return Err(RuleError::NoSuchCard(card));
+        };
+        if !player.can_afford(card) {
+            return Err(RuleError::NotEnoughAp {
+                cost: card.get_basic_info().cost,
+                available: player.get_ap_current(),
+            });
+        }
+
+        for (i, target) in targets.iter().enumerate() {
+            if i >= card.nb_targets() {
+                return Err(RuleError::InvalidTarget(*target));
+            }
+            self.validate_target(card, &targets[..i], target)?;
+        }
+        Ok(())
+    }
+
+    /// Check if the action can be applied on the current state without modifying it
+    pub fn validate(&self, action: &Action) -> Result<(), RuleError> {
+        if self.is_over() {
+            return Err(RuleError::MatchOver);
+        }
+
+        match action {
+            Action::PlayCard { card, targets } => {
+                self.check_targets(*card, targets)?;
+                let needed = self.active_player().hand[*card].nb_targets();
+                if targets.len() != needed {
+                    return Err(RuleError::MissingTargets {
+                        needed,
+                        given: targets.len(),
+                    });
+                }
+                Ok(())
+            }
+            Action::MoveCreature { creature, to } => {
+                self.expect_phase(Phase::Main)?;
+                match self.creature(*creature) {
+                    Some(found) if found.owner == self.turn.active_player => {}
+                    _ => return Err(RuleError::NotYourCreature(*creature)),
+                }
+                self.validate_tile(*to)?;
+                let reachable = self.reachable(*creature);
+                if reachable
+                    .and_then(|reachable| reachable.cost(*to))
+                    .is_none()
+                {
+                    return Err(RuleError::Unreachable(*to));
+                }
+                Ok(())
+            }
+            Action::Attack { attacker, target } => {
+                self.expect_phase(Phase::Combat)?;
+                let active_player = self.turn.active_player;
+                let creature = match self.creature(*attacker) {
+                    Some(creature) if creature.owner == active_player => creature,
+                    _ => return Err(RuleError::NotYourCreature(*attacker)),
+                };
+                if creature.summoning_sick {
+                    return Err(RuleError::SummoningSick(*attacker));
+                }
+                if creature.has_attacked {
+                    return Err(RuleError::AlreadyAttacked(*attacker));
+                }
+
+                match target {
+                    Target::Creature(id) => match self.creature(*id) {
+                        Some(defender)
+                            if defender.owner != active_player && defender.is_alive() =>
+                        {
+                            if !creature.in_range(defender.pos) {
+                                return Err(RuleError::TooFar(defender.pos));
+                            }
+                            Ok(())
+                        }
+                        _ => Err(RuleError::InvalidTarget(*target)),
+                    },
+                    Target::Player(player) if *player == Match::opponent(active_player) => {
+                        match self.closest_player_tile(*player, creature.pos) {
+                            Some(pos) if creature.in_range(pos) => Ok(()),
+                            Some(pos) => Err(RuleError::TooFar(pos)),
+                            None => Err(RuleError::InvalidTarget(*target)),
+                        }
+                    }
+                    _ => Err(RuleError::InvalidTarget(*target)),
+                }
+            }
+            Action::NextPhase | Action::EndTurn => {
+                if !self.turn.phase.is_interactive() {
+                    return Err(RuleError::WrongPhase(self.turn.phase));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Validate then apply the action on the state
+    ///
+    /// Return the events caused by the action, in the order they happened. If the
+    /// action is refused, the state is left untouched.
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, RuleError> {
+        self.validate(&action)?;
+
+        match action {
+            Action::PlayCard { card, targets } => self.play_card(card, targets),
+            Action::MoveCreature { creature, to } => self.walk(creature, to),
+            Action::Attack { attacker, target } => self.attack(attacker, target),
+            Action::NextPhase => self.next_phase(),
+            Action::EndTurn => self.end_turn(),
+        }
+        self.remove_dead();
+        self.check_victory();
+
+        Ok(std::mem::take(&mut self.events))
+    }
+
+    /// Check if the card can be played on the target, knowing the targets chosen before it
+    fn validate_target(
+        &self,
+        card: &Card,
+        previous: &[Target],
+        target: &Target,
+    ) -> Result<(), RuleError> {
+        let effect = match card {
+            Card::Creature(_) => None,
+            Card::Effect(effect_card) => Some(&effect_card.effect),
+        };
+
+        let active_player = self.turn.active_player;
+        match (effect.map(|effect| effect.effect_type), previous, target) {
+            // Summoning a creature
+            (None, [], Target::Tile(pos)) => self.validate_placement(*pos),
+            (
+                Some(effect_type @ (EffectType::Heal | EffectType::Damage)),
+                [],
+                Target::Creature(id),
+            ) => {
+                self.validate_creature(*id)?;
+                let owner = self
+                    .creature(*id)
+                    .map_or(active_player, |creature| creature.owner);
+                self.validate_side(effect_type, owner, target)
+            }
+            (
+                Some(effect_type @ (EffectType::Heal | EffectType::Damage)),
+                [],
+                Target::Player(player),
+            ) if *player < self.players.len() => self.validate_side(effect_type, *player, target),
+            // The creature to move
+            (Some(EffectType::Move), [], Target::Creature(id)) => self.validate_creature(*id),
+            // Where it goes
+            (Some(EffectType::Move), [Target::Creature(id)], Target::Tile(pos)) => {
+                self.validate_tile(*pos)?;
+                let range = effect.map_or(0, |effect| effect.nb);
+                let from = self.creature(*id).map_or(*pos, |creature| creature.pos);
+                if distance(from, *pos) > range {
+                    return Err(RuleError::TooFar(*pos));
+                }
+                Ok(())
+            }
+            // Tiles where the tokens are summoned, each one can only be chosen once
+            (Some(EffectType::Summon), _, Target::Tile(pos)) => {
+                if previous.contains(target) {
+                    return Err(RuleError::InvalidTarget(*target));
+                }
+                self.validate_placement(*pos)
+            }
+            _ => Err(RuleError::InvalidTarget(*target)),
+        }
+    }
+
+    /// Check if a heal or damage effect of the active player can affect the target
+    /// belonging to `owner`, see [`Match::friendly_fire`]
+    fn validate_side(
+        &self,
+        effect_type: EffectType,
+        owner: PlayerId,
+        target: &Target,
+    ) -> Result<(), RuleError> {
+        if self.can_affect(effect_type, self.turn.active_player, owner) {
+            return Ok(());
+        }
+        match effect_type {
+            EffectType::Damage => Err(RuleError::FriendlyFire(*target)),
+            _ => Err(RuleError::InvalidTarget(*target)),
+        }
+    }
+
+    /// Return if an effect of `player` can affect what belongs to `owner`
+    fn can_affect(&self, effect_type: EffectType, player: PlayerId, owner: PlayerId) -> bool {
+        self.friendly_fire
+            || match effect_type {
+                EffectType::Damage => owner != player,
+                EffectType::Heal => owner == player,
+                EffectType::Move | EffectType::Summon => true,
+            }
+    }
+
+    /// Check if the creature can be targeted
+    fn validate_creature(&self, id: CreatureId) -> Result<(), RuleError> {
+        match self.creature(id) {
+            Some(creature) if creature.is_alive() => Ok(()),
+            _ => Err(RuleError::InvalidTarget(Target::Creature(id))),
+        }
+    }
+
+    /// Check if a creature can be put on the tile: it is part of the map, can be walked
+    /// on and no creature stands on it
+    fn validate_tile(&self, pos: IVec2) -> Result<(), RuleError> {
+        if !self.world.within_map(pos) {
+            return Err(RuleError::OutOfMap(pos));
+        }
+        if !self.world.is_walkable(pos) {
+            return Err(RuleError::NotWalkable(pos));
+        }
+        if self.world.is_occupied(pos) {
+            return Err(RuleError::Occupied(pos));
+        }
+        Ok(())
+    }
+
+    /// Check if the active player can summon a creature on the tile
+    fn validate_placement(&self, pos: IVec2) -> Result<(), RuleError> {
+        self.validate_tile(pos)?;
+        if !self.world.in_deployment_zone(self.turn.active_player, pos) {
+            return Err(RuleError::OutsideDeploymentZone(pos));
+        }
+        Ok(())
+    }
+
+    fn expect_phase(&self, phase: Phase) -> Result<(), RuleError> {
+        if self.turn.phase != phase {
+            return Err(RuleError::WrongPhase(self.turn.phase));
+        }
+        Ok(())
+    }
+
+    /// Go to the next interactive phase, ending the turn if there is none left
+    fn next_phase(&mut self) {
+        match self.turn.phase.next() {
+            Some(phase) if phase.is_interactive() => self.turn.phase = phase,
+            _ => self.end_turn(),
+        }
+    }
+
+    /// Resolve the end of the current turn then start the turn of the opponent
+    fn end_turn(&mut self) {
+        self.turn.phase = Phase::End;
+        let player = self.turn.active_player;
+        self.stats.objectives_held[player] = if self.controls_objectives(player) {
+            self.stats.objectives_held[player] + 1
+        } else {
+            0
+        };
+
+        self.start_turn(Match::opponent(self.turn.active_player));
+    }
+
+    /// Resolve the automatic phases of a new turn until the player can act
+    fn start_turn(&mut self, player: PlayerId) {
+        self.turn.number += 1;
+        self.turn.active_player = player;
+        self.turn.phase = Phase::StartOfTurn;
+        self.players[player].refill_ap(AP_RAMP_PER_TURN, AP_CAP);
+        for creature in self.creatures.iter_mut() {
+            if creature.owner == player {
+                creature.movement_left = creature.card.movement;
+                creature.summoning_sick = false;
+                creature.has_attacked = false;
+            }
+        }
+
+        self.turn.phase = Phase::Draw;
+        self.draw(player, CARDS_DRAWN_PER_TURN);
+
+        self.turn.phase = Phase::Main;
+    }
+
+    /// Draw `nb` cards from the deck of the player into their hand and resolve
+    /// what happens if the deck is empty
+    fn draw(&mut self, player: PlayerId, nb: usize) {
+        let Player { deck, hand, .. } = &mut self.players[player];
+        for missed in deck.draw_into(nb, hand) {
+            match missed {
+                Draw::Card(_) => {}
+                Draw::Fatigue(damage)
+                    if !self.victory_conditions.contains(&VictoryCondition::DeckOut) =>
+                {
+                    let outcome = self.players[player].damage(damage);
+                    self.events.push(Event::PlayerDamaged { player, outcome });
+                }
+                Draw::Fatigue(_) | Draw::Loss => self.defeat(player, VictoryCondition::DeckOut),
+            }
+        }
+    }
+
+    /// Return if the player has a creature on every objective of the map
+    ///
+    /// Always false on maps without objectives.
+    pub fn controls_objectives(&self, player: PlayerId) -> bool {
+        !self.world.objectives.is_empty()
+            && self.world.objectives.iter().all(|objective| {
+                self.creature_at(*objective)
+                    .is_some_and(|creature| creature.owner == player)
+            })
+    }
+
+    /// End the match when a player fulfills one of the victory conditions, in the
+    /// order they are configured
+    ///
+    /// The deck-out is checked when the players draw.
+    fn check_victory(&mut self) {
+        for condition in self.victory_conditions.clone() {
+            // NOTE if both players are defeated at once, the first one loses
+            let loser = (0..self.players.len()).find(|player| match condition {
+                VictoryCondition::PlayerHealth => !self.players[*player].is_alive(),
+                VictoryCondition::HoldObjectives { turns } => {
+                    self.stats.objectives_held[Match::opponent(*player)] >= turns
+                }
+                VictoryCondition::Elimination => {
+                    self.stats.creatures_summoned[*player] > 0
+                        && self
+                            .creatures
+                            .iter()
+                            .all(|creature| creature.owner != *player)
+                }
+                VictoryCondition::DeckOut => false,
+            });
+            if let Some(loser) = loser {
+                self.defeat(loser, condition);
+            }
+        }
+    }
+
+    /// The player loses the match, unless it is already over
+    fn defeat(&mut self, player: PlayerId, condition: VictoryCondition) {
+        if self.result.is_none() {
+            self.result = Some(MatchResult {
+                loser: player,
+                condition,
+            });
+            self.events
+                .push(Event::PlayerDefeated { player, condition });
+        }
+    }
+
+    /// Walk the creature to the tile along the shortest path, it must be reachable
+    fn walk(&mut self, id: CreatureId, to: IVec2) {
+        let Some(reachable) = self.reachable(id) else {
+            return;
+        };
+        let (Some(cost), Some(path)) = (reachable.cost(to), reachable.path(to)) else {
+            return;
+        };
+        if let Some(creature) = self.creature_mut(id) {
+            creature.movement_left -= cost;
+            let from = creature.pos;
+            creature.pos = to;
+            self.world.vacate(from);
+            self.world.occupy(to, id);
+            self.events.push(Event::CreatureWalked { id, path });
+        }
+    }
+
+    /// Resolve the attack of a creature, the attacked creature retaliates if it survives
+    /// and the attacker is within its range
+    fn attack(&mut self, attacker: CreatureId, target: Target) {
+        let Some(creature) = self.creature_mut(attacker) else {
+            return;
+        };
+        creature.has_attacked = true;
+        let strike = Effect::new(EffectType::Damage, creature.card.attack);
+        let attacker_pos = creature.pos;
+
+        let player = creature.owner;
+        self.events
+            .push(Event::CreatureAttacked { attacker, target });
+        self.affect_target(&strike, target, player);
+
+        let Target::Creature(defender) = target else {
+            return;
+        };
+        let retaliation = self
+            .creature(defender)
+            .filter(|defender| defender.is_alive() && defender.in_range(attacker_pos))
+            .map(|defender| Effect::new(EffectType::Damage, defender.card.attack));
+        if let Some(strike) = retaliation {
+            self.events
+                .push(Event::CreatureRetaliated { defender, attacker });
+            self.affect_target(&strike, Target::Creature(attacker), Match::opponent(player));
+        }
+    }
+
+    fn play_card(&mut self, index: usize, targets: Vec<Target>) {
+        let player = self.turn.active_player;
+        let card = self.players[player].hand.remove(index);
+        self.players[player].spend_ap(card.get_basic_info().cost);
+        self.stats.cards_played[player] += 1;
+
+        match card {
+            Card::Creature(creature_card) => {
+                if let [Target::Tile(pos)] = targets[..] {
+                    self.summon(creature_card, player, pos);
+                }
+            }
+            Card::Effect(effect_card) => {
+                self.resolve(effect_card.effect.clone().on(targets), player);
+                self.players[player].deck.discard(Card::Effect(effect_card));
+            }
+        }
+    }
+
+    /// Apply an effect played by `player` on its targets
+    fn resolve(&mut self, targetted: TargettedCardEffect, player: PlayerId) {
+        let TargettedCardEffect { effect, targets } = targetted;
+        match (effect.effect_type, &targets[..]) {
+            (EffectType::Move, [Target::Creature(id), Target::Tile(pos)]) => {
+                if let Some(creature) = self.creature_mut(*id) {
+                    let from = creature.pos;
+                    creature.pos = *pos;
+                    self.world.vacate(from);
+                    self.world.occupy(*pos, *id);
+                    self.events.push(Event::CreatureMoved {
+                        id: *id,
+                        from,
+                        to: *pos,
+                    });
+                }
+            }
+            (EffectType::Summon, _) => {
+                if let Some(token) = &effect.token {
+                    for target in &targets {
+                        if let Target::Tile(pos) = target {
+                            let id = self.summon((**token).clone(), player, *pos);
+                            if let Some(creature) = self.creature_mut(id) {
+                                creature.is_token = true;
+                            }
+                        }
+                    }
+                }
+            }
+            (EffectType::Heal | EffectType::Damage, _) => {
+                for target in &targets {
+                    self.affect_target(&effect, *target, player);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply a heal or damage effect of `player` on a creature or a player
+    fn affect_target(&mut self, effect: &Effect, target: Target, player: PlayerId) {
+        let owner = match target {
+            Target::Creature(id) => self.creature(id).map_or(player, |creature| creature.owner),
+            Target::Player(owner) => owner,
+            Target::Tile(_) => player,
+        };
+        let event = match target {
+            Target::Creature(id) => {
+                let Some(creature) = self.creature_mut(id) else {
+                    return;
+                };
+                match effect.affect_target(creature) {
+                    Some(LifeChange::Healed(amount)) => Event::CreatureHealed { id, amount },
+                    Some(LifeChange::Damaged(outcome)) => Event::CreatureDamaged { id, outcome },
+                    None => return,
+                }
+            }
+            Target::Player(target) => match effect.affect_target(&mut self.players[target]) {
+                Some(LifeChange::Healed(amount)) => Event::PlayerHealed {
+                    player: target,
+                    amount,
+                },
+                Some(LifeChange::Damaged(outcome)) => Event::PlayerDamaged {
+                    player: target,
+                    outcome,
+                },
+                None => return,
+            },
+            Target::Tile(_) => return,
+        };
+
+        match event {
+            Event::CreatureHealed { amount, .. } | Event::PlayerHealed { amount, .. } => {
+                self.stats.healing_done[player] += amount;
+            }
+            Event::CreatureDamaged { outcome, .. } | Event::PlayerDamaged { outcome, .. }
+                if owner != player =>
+            {
+                self.stats.damage_dealt[player] += outcome.dealt;
+            }
+            _ => {}
+        }
+        self.events.push(event);
+    }
+
+    /// Remove the dead creatures from the board and trigger their on-death effects
+    ///
+    /// The card of a dead creature goes to the discard pile of its owner, tokens vanish.
+    /// On-death effects can kill other creatures, they are removed in the same pass.
+    fn remove_dead(&mut self) {
+        while let Some(index) = self.creatures.iter().position(|c| !c.is_alive()) {
+            let Creature {
+                id,
+                card,
+                owner,
+                pos,
+                is_token,
+                ..
+            } = self.creatures.remove(index);
+            self.world.vacate(pos);
+            self.stats.creatures_lost[owner] += 1;
+            self.events.push(Event::CreatureDied { id, owner, pos });
+
+            let on_death = card.on_death.clone();
+            if !is_token {
+                self.players[owner].deck.discard(Card::Creature(card));
+            }
+            if let Some(effect) = on_death {
+                self.trigger_on_death(effect, owner, pos);
+            }
+        }
+    }
+
+    /// Resolve the on-death effect of a creature of `owner` which died at `pos`
+    ///
+    /// There is no one to choose the targets, so they are deduced from where it died:
+    /// * Heal and Damage: every creature next to it that the effect can affect, see
+    ///   [`Match::friendly_fire`]
+    /// * Summon: the tile where it died then the free tiles next to it, the deployment
+    ///   zones are ignored since the tokens are not played from the hand
+    /// * Move: nothing
+    fn trigger_on_death(&mut self, effect: Effect, owner: PlayerId, pos: IVec2) {
+        let neighbours = [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y].map(|dir| pos + dir);
+
+        let targets: Vec<Target> = match effect.effect_type {
+            EffectType::Heal | EffectType::Damage => self
+                .creatures
+                .iter()
+                .filter(|creature| distance(creature.pos, pos) == 1)
+                .filter(|creature| self.can_affect(effect.effect_type, owner, creature.owner))
+                .map(|creature| Target::Creature(creature.id))
+                .collect(),
+            EffectType::Summon => std::iter::once(pos)
+                .chain(neighbours)
+                .filter(|tile| self.validate_tile(*tile).is_ok())
+                .take(effect.nb as usize)
+                .map(Target::Tile)
+                .collect(),
+            EffectType::Move => Vec::new(),
+        };
+        self.resolve(effect.on(targets), owner);
+    }
+
+    fn summon(&mut self, card: CreatureCard, owner: PlayerId, pos: IVec2) -> CreatureId {
+        let id = self.next_creature_id;
+        self.next_creature_id += 1;
+        self.creatures.push(Creature::new(id, card, owner, pos));
+        self.stats.creatures_summoned[owner] += 1;
+        self.world.occupy(pos, id);
+        self.events.push(Event::CreatureSummoned { id, owner, pos });
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{ivec2, Vec4};
+
+    use super::*;
+    use crate::game::{
+        deck::Deck,
+        effect::Effect,
+        life::DamageOutcome,
+        rules::{CardBasicInfo, EffectCard},
+        world::{DeploymentZone, SpawnPoint},
+    };
+
+    fn basic_info(name: &str) -> CardBasicInfo {
+        CardBasicInfo {
+            name: name.to_string(),
+            description: String::new(),
+            cost: 0,
+            card_color: Vec4::ONE,
+        }
+    }
+
+    fn creature_card(name: &str) -> CreatureCard {
+        CreatureCard::new(basic_info(name), 3, 1, 1, 2, "")
+    }
+
+    fn effect_card(effect: Effect) -> Card {
+        Card::Effect(EffectCard::new(basic_info("Effect"), effect))
+    }
+
+    /// Match on a flat 5x5 board, player 0 deploys on the two first rows and player 1
+    /// on the two last ones. Player 0 is in their main phase with `hand`.
+    fn new_match(hand: Vec<Card>) -> Match {
+        let mut world = World::new(5, 5);
+        world.deployment_zones = vec![
+            DeploymentZone {
+                player: 0,
+                min: ivec2(0, 0),
+                max: ivec2(4, 1),
+            },
+            DeploymentZone {
+                player: 1,
+                min: ivec2(0, 3),
+                max: ivec2(4, 4),
+            },
+        ];
+        let players = [0, 1].map(|id| {
+            let mut player = Player::new(id, format!("Player {}", id + 1));
+            let cards = vec![Card::Creature(creature_card("Filler")); 10];
+            player.deck = Deck::new(id, cards, 0);
+            player
+        });
+        let mut game = Match::new(world, players);
+        game.players[0].hand = hand;
+        game
+    }
+
+    fn play(card: usize, targets: Vec<Target>) -> Action {
+        Action::PlayCard { card, targets }
+    }
+
+    /// Creature of player 0 ready to attack, during the combat phase
+    fn attacker(game: &mut Match, pos: IVec2) -> CreatureId {
+        let id = game.summon(creature_card("Goblin"), 0, pos);
+        game.creature_mut(id).unwrap().summoning_sick = false;
+        game.apply(Action::NextPhase).unwrap();
+        id
+    }
+
+    #[test]
+    fn end_turn_passes_the_turn_to_the_opponent() {
+        let mut game = new_match(Vec::new());
+        let turn = |game: &Match| (game.turn.number, game.turn.active_player, game.turn.phase);
+        assert_eq!(turn(&game), (1, 0, Phase::Main));
+
+        game.apply(Action::NextPhase).unwrap();
+        assert_eq!(turn(&game), (1, 0, Phase::Combat));
+        game.apply(Action::NextPhase).unwrap();
+        assert_eq!(turn(&game), (2, 1, Phase::Main));
+        game.apply(Action::EndTurn).unwrap();
+        assert_eq!(turn(&game), (3, 0, Phase::Main));
+    }
+
+    #[test]
+    fn cards_cost_action_points() {
+        let mut ogre = creature_card("Ogre");
+        ogre.basic_info.cost = 2;
+        let mut goblin = creature_card("Goblin");
+        goblin.basic_info.cost = 1;
+        let mut game = new_match(vec![Card::Creature(ogre), Card::Creature(goblin)]);
+        assert_eq!(game.players[0].get_ap_current(), 1);
+
+        let tile = vec![Target::Tile(ivec2(0, 0))];
+        assert_eq!(
+            game.apply(play(0, tile.clone())),
+            Err(RuleError::NotEnoughAp {
+                cost: 2,
+                available: 1
+            })
+        );
+        game.apply(play(1, tile)).unwrap();
+        assert_eq!(game.players[0].get_ap_current(), 0);
+
+        // Refilled with one more action point on the next turn of the player
+        game.apply(Action::EndTurn).unwrap();
+        game.apply(Action::EndTurn).unwrap();
+        assert_eq!(game.players[0].get_ap_current(), 2);
+        assert_eq!(game.players[0].get_ap_max(), 2);
+    }
+
+    #[test]
+    fn dead_creatures_are_removed_with_an_event() {
+        let strike = effect_card(Effect::new(EffectType::Damage, 5));
+        let mut game = new_match(vec![strike]);
+        let pos = ivec2(1, 3);
+        let id = game.summon(creature_card("Goblin"), 1, pos);
+
+        let events = game.apply(play(0, vec![Target::Creature(id)])).unwrap();
+        let outcome = DamageOutcome {
+            dealt: 3,
+            overkill: 2,
+            died: true,
+        };
+        assert!(events.contains(&Event::CreatureDamaged { id, outcome }));
+        assert!(events.contains(&Event::CreatureDied { id, owner: 1, pos }));
+        assert!(game.creatures.is_empty());
+        assert!(!game.world.is_occupied(pos));
+        assert_eq!(game.players[1].deck.discard_pile.len(), 1);
+    }
+
+    #[test]
+    fn attacks_need_a_ready_creature_in_range() {
+        let mut game = new_match(Vec::new());
+        let id = attacker(&mut game, ivec2(1, 1));
+        let sick = game.summon(creature_card("Goblin"), 0, ivec2(3, 1));
+        let near = game.summon(creature_card("Goblin"), 1, ivec2(1, 2));
+        let far = game.summon(creature_card("Goblin"), 1, ivec2(1, 4));
+
+        let attack = |attacker, target| Action::Attack {
+            attacker,
+            target: Target::Creature(target),
+        };
+        assert_eq!(
+            game.validate(&attack(sick, near)),
+            Err(RuleError::SummoningSick(sick))
+        );
+        assert_eq!(
+            game.validate(&attack(id, far)),
+            Err(RuleError::TooFar(ivec2(1, 4)))
+        );
+        game.apply(attack(id, near)).unwrap();
+        assert_eq!(
+            game.validate(&attack(id, near)),
+            Err(RuleError::AlreadyAttacked(id))
+        );
+    }
+
+    #[test]
+    fn defenders_in_range_retaliate() {
+        let mut game = new_match(Vec::new());
+        let id = attacker(&mut game, ivec2(1, 1));
+        let defender = game.summon(creature_card("Goblin"), 1, ivec2(1, 2));
+
+        let events = game
+            .apply(Action::Attack {
+                attacker: id,
+                target: Target::Creature(defender),
+            })
+            .unwrap();
+        assert!(events.contains(&Event::CreatureRetaliated {
+            defender,
+            attacker: id
+        }));
+        let hp = |id| game.creature(id).map(|creature| creature.hp_current);
+        assert_eq!(hp(id), Some(2));
+        assert_eq!(hp(defender), Some(2));
+    }
+
+    #[test]
+    fn players_are_attacked_from_their_spawn_point() {
+        let mut game = new_match(Vec::new());
+        game.world.spawn_points = vec![SpawnPoint {
+            player: 1,
+            pos: ivec2(2, 4),
+        }];
+        let far = attacker(&mut game, ivec2(2, 0));
+        let near = game.summon(creature_card("Goblin"), 0, ivec2(2, 3));
+        game.creature_mut(near).unwrap().summoning_sick = false;
+
+        let target = Target::Player(1);
+        let from_far = Action::Attack {
+            attacker: far,
+            target,
+        };
+        assert_eq!(
+            game.validate(&from_far),
+            Err(RuleError::TooFar(ivec2(2, 4)))
+        );
+        let from_near = Action::Attack {
+            attacker: near,
+            target,
+        };
+        assert_eq!(game.validate(&from_near), Ok(()));
+    }
+
+    #[test]
+    fn players_without_spawn_point_are_attacked_from_their_zone() {
+        let mut game = new_match(Vec::new());
+        let id = attacker(&mut game, ivec2(0, 2));
+        assert_eq!(game.closest_player_tile(1, ivec2(0, 2)), Some(ivec2(0, 3)));
+        assert_eq!(game.attack_targets(id), vec![Target::Player(1)]);
+    }
+
+    #[test]
+    fn move_is_limited_by_the_range_of_the_effect() {
+        let mut game = new_match(vec![effect_card(Effect::new(EffectType::Move, 2))]);
+        let id = game.summon(creature_card("Goblin"), 0, ivec2(1, 1));
+
+        let too_far = play(0, vec![Target::Creature(id), Target::Tile(ivec2(1, 4))]);
+        assert_eq!(game.validate(&too_far), Err(RuleError::TooFar(ivec2(1, 4))));
+    }
+
+    #[test]
+    fn move_is_refused_onto_an_occupied_tile() {
+        let mut game = new_match(vec![effect_card(Effect::new(EffectType::Move, 2))]);
+        let id = game.summon(creature_card("Goblin"), 0, ivec2(1, 1));
+        game.summon(creature_card("Goblin"), 1, ivec2(2, 1));
+
+        let occupied = play(0, vec![Target::Creature(id), Target::Tile(ivec2(2, 1))]);
+        assert_eq!(
+            game.validate(&occupied),
+            Err(RuleError::Occupied(ivec2(2, 1)))
+        );
+    }
+
+    #[test]
+    fn move_changes_the_occupied_tile() {
+        let mut game = new_match(vec![effect_card(Effect::new(EffectType::Move, 2))]);
+        let id = game.summon(creature_card("Goblin"), 0, ivec2(1, 1));
+
+        game.apply(play(
+            0,
+            vec![Target::Creature(id), Target::Tile(ivec2(1, 3))],
+        ))
+        .unwrap();
+        assert_eq!(game.world.occupant(ivec2(1, 1)), None);
+        assert_eq!(game.world.occupant(ivec2(1, 3)), Some(id));
+        assert_eq!(
+            game.creature(id).map(|creature| creature.pos),
+            Some(ivec2(1, 3))
+        );
+    }
+
+    #[test]
+    fn summon_refuses_the_same_tile_twice() {
+        let summon = Effect::summon(2, creature_card("Token"));
+        let game = new_match(vec![effect_card(summon)]);
+
+        let twice = play(0, vec![Target::Tile(ivec2(0, 0)); 2]);
+        assert_eq!(
+            game.validate(&twice),
+            Err(RuleError::InvalidTarget(Target::Tile(ivec2(0, 0))))
+        );
+    }
+
+    #[test]
+    fn summon_is_limited_to_the_deployment_zone() {
+        let summon = Effect::summon(1, creature_card("Token"));
+        let game = new_match(vec![effect_card(summon)]);
+
+        let outside = play(0, vec![Target::Tile(ivec2(2, 2))]);
+        assert_eq!(
+            game.validate(&outside),
+            Err(RuleError::OutsideDeploymentZone(ivec2(2, 2)))
+        );
+        assert_eq!(
+            game.validate(&play(0, vec![Target::Tile(ivec2(2, 1))])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn on_death_summons_ignore_the_deployment_zones() {
+        let mut game = new_match(Vec::new());
+        let card = creature_card("Goblin").with_on_death(Effect::summon(1, creature_card("Token")));
+        let id = game.summon(card, 0, ivec2(2, 2));
+        game.creature_mut(id).unwrap().hp_current = 0;
+        game.remove_dead();
+
+        let token = game
+            .creature_at(ivec2(2, 2))
+            .expect("the token replaces the goblin");
+        assert!(token.is_token);
+    }
+
+    #[test]
+    fn summon_creates_tokens() {
+        let summon = Effect::summon(2, creature_card("Token"));
+        let mut game = new_match(vec![effect_card(summon)]);
+
+        let tiles = [ivec2(0, 0), ivec2(1, 0)];
+        game.apply(play(0, tiles.map(Target::Tile).to_vec()))
+            .unwrap();
+        assert_eq!(game.creatures.len(), 2);
+        for (creature, pos) in game.creatures.iter().zip(tiles) {
+            assert!(creature.is_token);
+            assert_eq!(creature.pos, pos);
+            assert_eq!(game.world.occupant(pos), Some(creature.id));
+        }
+    }
+
+    #[test]
+    fn dead_tokens_do_not_go_to_the_discard_pile() {
+        let summon = Effect::summon(1, creature_card("Token"));
+        let mut game = new_match(vec![effect_card(summon)]);
+        game.apply(play(0, vec![Target::Tile(ivec2(0, 0))]))
+            .unwrap();
+        game.summon(creature_card("Goblin"), 0, ivec2(1, 0));
+        // The summon card itself
+        let discarded = game.players[0].deck.discard_pile.len();
+
+        for creature in game.creatures.iter_mut() {
+            creature.hp_current = 0;
+        }
+        game.remove_dead();
+        let discard_pile = &game.players[0].deck.discard_pile;
+        assert!(game.creatures.is_empty());
+        assert_eq!(discard_pile.len(), discarded + 1);
+        assert_eq!(discard_pile.last().map(Card::get_name), Some("Goblin"));
+        assert!(!game.world.is_occupied(ivec2(0, 0)));
+    }
+
+    #[test]
+    fn the_player_without_health_loses() {
+        let strike = effect_card(Effect::new(EffectType::Damage, 100));
+        let mut game = new_match(vec![strike]);
+        game.apply(play(0, vec![Target::Player(1)])).unwrap();
+        assert_eq!(
+            game.result,
+            Some(MatchResult {
+                loser: 1,
+                condition: VictoryCondition::PlayerHealth
+            })
+        );
+        assert_eq!(game.apply(Action::EndTurn), Err(RuleError::MatchOver));
+    }
+
+    #[test]
+    fn effects_stay_on_their_side() {
+        let strike = effect_card(Effect::new(EffectType::Damage, 1));
+        let heal = effect_card(Effect::new(EffectType::Heal, 1));
+        let mut game = new_match(vec![strike, heal]);
+        let own = game.summon(creature_card("Goblin"), 0, ivec2(0, 0));
+        let enemy = game.summon(creature_card("Goblin"), 1, ivec2(0, 4));
+
+        let on_own = vec![Target::Creature(own)];
+        let on_enemy = vec![Target::Creature(enemy)];
+        assert_eq!(
+            game.validate(&play(0, on_own.clone())),
+            Err(RuleError::FriendlyFire(on_own[0]))
+        );
+        assert_eq!(
+            game.validate(&play(1, on_enemy.clone())),
+            Err(RuleError::InvalidTarget(on_enemy[0]))
+        );
+        assert_eq!(game.validate(&play(0, on_enemy)), Ok(()));
+        assert_eq!(game.validate(&play(1, on_own.clone())), Ok(()));
+
+        game.friendly_fire = true;
+        assert_eq!(game.validate(&play(0, on_own)), Ok(()));
+    }
+}
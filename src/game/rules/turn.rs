@@ -0,0 +1,97 @@
+use std::fmt;
+
+use crate::game::player::PlayerId;
+
+/// Phases of a turn, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Effects happening at the start of the turn
+    StartOfTurn,
+    /// The active player draws
+    Draw,
+    /// The active player can play cards
+    Main,
+    /// The active player's creatures can fight
+    Combat,
+    /// Effects happening at the end of the turn
+    End,
+}
+
+impl Phase {
+    /// Return the phase following this one, `None` once the turn is over
+    pub fn next(self) -> Option<Phase> {
+        match self {
+            Phase::StartOfTurn => Some(Phase::Draw),
+            Phase::Draw => Some(Phase::Main),
+            Phase::Main => Some(Phase::Combat),
+            Phase::Combat => Some(Phase::End),
+            Phase::End => None,
+        }
+    }
+
+    /// Return if the active player gets to act during this phase.
+    ///
+    /// The other phases are resolved automatically by the rules.
+    pub fn is_interactive(self) -> bool {
+        matches!(self, Phase::Main | Phase::Combat)
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::StartOfTurn => "Start of turn",
+            Phase::Draw => "Draw",
+            Phase::Main => "Main",
+            Phase::Combat => "Combat",
+            Phase::End => "End",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Current turn of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    /// Number of the turn, starting at 1. Each player's turn counts.
+    pub number: u32,
+    /// Player allowed to act during this turn
+    pub active_player: PlayerId,
+    pub phase: Phase,
+}
+
+impl Turn {
+    /// Turn before the first one, the match starts it right away
+    pub fn new(first_player: PlayerId) -> Self {
+        Turn {
+            number: 0,
+            active_player: first_player,
+            phase: Phase::End,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_follow_each_other() {
+        let mut phases = vec![Phase::StartOfTurn];
+        while let Some(next) = phases.last().unwrap().next() {
+            phases.push(next);
+        }
+        assert_eq!(
+            phases,
+            [
+                Phase::StartOfTurn,
+                Phase::Draw,
+                Phase::Main,
+                Phase::Combat,
+                Phase::End
+            ]
+        );
+        let interactive: Vec<Phase> = phases.into_iter().filter(|p| p.is_interactive()).collect();
+        assert_eq!(interactive, [Phase::Main, Phase::Combat]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::player::PlayerId;
+
+/// Way for a player to win the match, see [`super::Match::victory_conditions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VictoryCondition {
+    /// The health of the opponent drops to 0
+    PlayerHealth,
+    /// The player controls every objective of the map at the end of `turns` of their
+    /// turns in a row. A creature controls the objective it stands on.
+    ///
+    /// Ignored on maps without objectives.
+    HoldObjectives { turns: u32 },
+    /// The last creature of the opponent dies. A player who has not summoned any
+    /// creature yet cannot be eliminated.
+    Elimination,
+    /// The opponent must draw from an empty deck with nothing left to reshuffle,
+    /// whatever the [`crate::game::deck::EmptyDeckRule`] of their deck
+    DeckOut,
+}
+
+impl VictoryCondition {
+    /// Conditions of a match unless configured otherwise
+    ///
+    /// `NOTE` holding the objectives is not part of them: on maps whose objectives are
+    /// reached on the first turn, the first player wins almost every match with it.
+    pub const DEFAULT: [VictoryCondition; 1] = [VictoryCondition::PlayerHealth];
+    /// Turns of [`VictoryCondition::HoldObjectives`] when not given
+    pub const DEFAULT_HOLD_TURNS: u32 = 3;
+
+    /// Parse conditions separated by commas (ex: "health,objectives:5"), see
+    /// [`VictoryCondition::from_str`]
+    pub fn parse_list(list: &str) -> Result<Vec<VictoryCondition>, String> {
+        let conditions: Vec<VictoryCondition> = list
+            .split(',')
+            .map(|condition| condition.trim().parse())
+            .collect::<Result<_, _>>()?;
+        if conditions.is_empty() {
+            return Err("no victory condition, the match would never end".to_string());
+        }
+        Ok(conditions)
+    }
+}
+
+impl fmt::Display for VictoryCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VictoryCondition::PlayerHealth => write!(f, "health reduced to 0"),
+            VictoryCondition::HoldObjectives { turns } => {
+                write!(f, "objectives held for {} turns", turns)
+            }
+            VictoryCondition::Elimination => write!(f, "every creature eliminated"),
+            VictoryCondition::DeckOut => write!(f, "no card left to draw"),
+        }
+    }
+}
+
+impl FromStr for VictoryCondition {
+    type Err = String;
+
+    /// Parse a condition as written on the command line: "health", "elimination",
+    /// "deckout" or "objectives" followed by the number of turns (ex: "objectives:5")
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let (name, turns) = match name.split_once(':') {
+            Some((name, turns)) => (name, Some(turns)),
+            None => (name, None),
+        };
+        match (name.to_ascii_lowercase().as_str(), turns) {
+            ("health", None) => Ok(VictoryCondition::PlayerHealth),
+            ("elimination", None) => Ok(VictoryCondition::Elimination),
+            ("deckout", None) => Ok(VictoryCondition::DeckOut),
+            ("objectives", turns) => {
+                let turns = match turns {
+                    Some(turns) => turns
+                        .parse()
+                        .ok()
+                        .filter(|turns| *turns > 0)
+                        .ok_or_else(|| format!("invalid number of turns \"{}\"", turns))?,
+                    None => VictoryCondition::DEFAULT_HOLD_TURNS,
+                };
+                Ok(VictoryCondition::HoldObjectives { turns })
+            }
+            _ => Err(format!("unknown victory condition \"{}\"", name)),
+        }
+    }
+}
+
+/// How the match ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchResult {
+    pub loser: PlayerId,
+    /// Condition fulfilled by the winner
+    pub condition: VictoryCondition,
+}
+
+impl MatchResult {
+    pub fn winner(&self) -> PlayerId {
+        1 - self.loser
+    }
+}
+
+/// What each player did during the match, indexed by [`PlayerId`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub cards_played: [u32; 2],
+    pub creatures_summoned: [u32; 2],
+    pub creatures_lost: [u32; 2],
+    /// Damage dealt to the opponent and their creatures
+    pub damage_dealt: [u32; 2],
+    /// Health healed on the player and their creatures
+    pub healing_done: [u32; 2],
+    /// Turns in a row each player ended while controlling every objective
+    pub objectives_held: [u32; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{ivec2, IVec2, Vec4};
+
+    use super::*;
+    use crate::game::{
+        deck::Deck,
+        effect::{Effect, EffectType, Target},
+        player::Player,
+        rules::{Action, Card, CardBasicInfo, CreatureCard, EffectCard, Match},
+        world::{DeploymentZone, World},
+    };
+
+    fn basic_info(name: &str) -> CardBasicInfo {
+        CardBasicInfo {
+            name: name.to_string(),
+            description: String::new(),
+            cost: 0,
+            card_color: Vec4::ONE,
+        }
+    }
+
+    fn creature() -> Card {
+        Card::Creature(CreatureCard::new(basic_info("Goblin"), 3, 1, 1, 2, ""))
+    }
+
+    /// Match on a flat 5x5 board with an objective in the middle, inside the zone of
+    /// player 0. Each deck has `deck_size` creatures, the hands are emptied.
+    fn new_match(conditions: &[VictoryCondition], deck_size: usize) -> Match {
+        let mut world = World::new(5, 5);
+        world.deployment_zones = vec![
+            DeploymentZone {
+                player: 0,
+                min: ivec2(0, 0),
+                max: ivec2(4, 2),
+            },
+            DeploymentZone {
+                player: 1,
+                min: ivec2(0, 3),
+                max: ivec2(4, 4),
+            },
+        ];
+        world.objectives = vec![ivec2(2, 2)];
+        let players = [0, 1].map(|id| {
+            let mut player = Player::new(id, format!("Player {}", id + 1));
+            player.deck = Deck::new(id, vec![creature(); deck_size], 0);
+            player
+        });
+        let mut game = Match::new(world, players);
+        game.victory_conditions = conditions.to_vec();
+        for player in &mut game.players {
+            player.hand.clear();
+        }
+        game
+    }
+
+    /// The active player plays a card from their hand
+    fn play(game: &mut Match, card: Card, targets: Vec<Target>) {
+        let hand = &mut game.players[game.turn.active_player].hand;
+        hand.push(card);
+        let card = hand.len() - 1;
+        game.apply(Action::PlayCard { card, targets }).unwrap();
+    }
+
+    fn summon(game: &mut Match, pos: IVec2) {
+        play(game, creature(), vec![Target::Tile(pos)]);
+    }
+
+    #[test]
+    fn parses_the_command_line() {
+        assert_eq!(
+            VictoryCondition::parse_list("health, objectives:5,deckout"),
+            Ok(vec![
+                VictoryCondition::PlayerHealth,
+                VictoryCondition::HoldObjectives { turns: 5 },
+                VictoryCondition::DeckOut,
+            ])
+        );
+        assert_eq!(
+            "objectives".parse(),
+            Ok(VictoryCondition::HoldObjectives {
+                turns: VictoryCondition::DEFAULT_HOLD_TURNS
+            })
+        );
+        assert!("objectives:0".parse::<VictoryCondition>().is_err());
+        assert!(VictoryCondition::parse_list("health,castle").is_err());
+    }
+
+    #[test]
+    fn objectives_held_for_the_turns_win() {
+        let condition = VictoryCondition::HoldObjectives { turns: 2 };
+        let mut game = new_match(&[condition], 10);
+        summon(&mut game, ivec2(2, 2));
+        game.apply(Action::EndTurn).unwrap();
+        game.apply(Action::EndTurn).unwrap();
+        assert!(!game.is_over());
+
+        game.apply(Action::EndTurn).unwrap();
+        assert_eq!(
+            game.result.map(|r| (r.winner(), r.condition)),
+            Some((0, condition))
+        );
+    }
+
+    #[test]
+    fn losing_the_last_creature_loses() {
+        let mut game = new_match(&[VictoryCondition::Elimination], 10);
+        game.apply(Action::EndTurn).unwrap();
+        summon(&mut game, ivec2(2, 4));
+        game.apply(Action::EndTurn).unwrap();
+        assert!(!game.is_over(), "player 0 has not summoned anything yet");
+
+        let id = game.creatures[0].id;
+        let strike = Card::Effect(EffectCard::new(
+            basic_info("Strike"),
+            Effect::new(EffectType::Damage, 10),
+        ));
+        play(&mut game, strike, vec![Target::Creature(id)]);
+        let result = game.result.map(|r| (r.winner(), r.condition));
+        assert_eq!(result, Some((0, VictoryCondition::Elimination)));
+    }
+
+    #[test]
+    fn drawing_from_an_empty_deck_loses() {
+        // The opening hand and the first draw of each player empty their decks
+        let mut game = new_match(&[VictoryCondition::DeckOut], 4);
+        game.apply(Action::EndTurn).unwrap();
+        assert!(!game.is_over());
+
+        game.apply(Action::EndTurn).unwrap();
+        let result = game.result.map(|r| (r.winner(), r.condition));
+        assert_eq!(result, Some((1, VictoryCondition::DeckOut)));
+    }
+}
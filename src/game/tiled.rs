@@ -0,0 +1,688 @@
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+
+/// Bits of a global tile id used by Tiled to flip the tile
+const GID_FLAGS: u32 = 0xE000_0000;
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+
+/// Map made with Tiled, read from a TMX file or exported in JSON
+///
+/// Only the parts used by the game are read.
+/// Reference: https://doc.mapeditor.org/en/stable/reference/json-map-format/
+#[derive(Debug, Clone, Deserialize)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tilewidth: u32,
+    pub tileheight: u32,
+    pub layers: Vec<Layer>,
+    pub tilesets: Vec<Tileset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    /// "tilelayer", "objectgroup" or "imagelayer". The groups are replaced by their
+    /// layers once the map is read.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Global tile ids of the cells, row by row. 0 is an empty cell
+    #[serde(default)]
+    pub data: Vec<u32>,
+    /// Objects of an object layer
+    #[serde(default)]
+    pub objects: Vec<Object>,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    /// Offset of the whole layer in pixels
+    #[serde(default)]
+    pub offsetx: f32,
+    #[serde(default)]
+    pub offsety: f32,
+    /// Layers of a group, from the bottom one to the top one
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+}
+
+/// Object of an object layer: a tile, a point, a rectangle, etc.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Object {
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    /// Class of the object, named "type" before Tiled 1.9
+    #[serde(default, rename = "type", alias = "class")]
+    pub class: String,
+    /// Position in pixels. On isometric maps, a tile is `tileheight` pixels along
+    /// each axis of the map.
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub width: f32,
+    #[serde(default)]
+    pub height: f32,
+    /// Global tile id of a tile object
+    #[serde(default)]
+    pub gid: Option<u32>,
+    #[serde(default)]
+    pub point: bool,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tileset {
+    /// Global id of the first tile of the tileset
+    pub firstgid: u32,
+    /// File of an external tileset, relative to the map file. The rest of the tileset is
+    /// empty until it is read, see [`Tileset::from_tsx`].
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub name: String,
+    /// Tiles with custom data, the others are not listed
+    #[serde(default)]
+    pub tiles: Vec<Tile>,
+    /// Image containing every tile, relative to the map file
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Number of tiles on a row of the image
+    #[serde(default)]
+    pub columns: u32,
+    #[serde(default)]
+    pub tilewidth: u32,
+    #[serde(default)]
+    pub tileheight: u32,
+    /// Space around the tiles of the image, in pixels
+    #[serde(default)]
+    pub margin: u32,
+    /// Space between the tiles of the image, in pixels
+    #[serde(default)]
+    pub spacing: u32,
+    /// Offset applied when drawing the tiles, in pixels
+    #[serde(default)]
+    pub tileoffset: TileOffset,
+    /// Point of a tile object placed at its position: "bottom", "topleft", etc.
+    /// "unspecified" is "bottom" on isometric maps.
+    #[serde(default)]
+    pub objectalignment: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct TileOffset {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tile {
+    /// Id of the tile in its tileset
+    pub id: u32,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+/// Custom property set on a tile in Tiled
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Property {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+fn default_opacity() -> f32 {
+    1.
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+/// Reason why a map could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    /// The file of the map or of one of its tilesets could not be read
+    MissingFile(String),
+    /// The file is not a valid Tiled map
+    Parse(String),
+    /// The map has no tile layer to build the world from
+    NoTileLayer,
+    /// A tile layer does not have one tile per cell of the map
+    LayerSize {
+        layer: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A property of a tile does not have the expected type
+    InvalidProperty { tile: u32, name: String },
+    /// A gameplay object of an object layer is not valid
+    InvalidObject { object: u32, reason: String },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::MissingFile(file) => write!(f, "{} could not be read", file),
+            MapError::Parse(error) => write!(f, "invalid Tiled map: {}", error),
+            MapError::NoTileLayer => write!(f, "the map has no tile layer"),
+            MapError::LayerSize {
+                layer,
+                expected,
+                found,
+            } => write!(
+                f,
+                "layer \"{}\" has {} tiles instead of {}",
+                layer, found, expected
+            ),
+            MapError::InvalidObject { object, reason } => {
+                write!(f, "object {} is invalid: {}", object, reason)
+            }
+            MapError::InvalidProperty { tile, name } => {
+                write!(
+                    f,
+                    "property \"{}\" of tile {} has the wrong type",
+                    name, tile
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl TiledMap {
+    /// Read a map and its external tilesets from the disk, without their images
+    pub fn load(path: &Path) -> Result<TiledMap, MapError> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map_err(|_| MapError::MissingFile(path.to_string_lossy().into_owned()))
+        };
+        let mut map = TiledMap::parse(&path.to_string_lossy(), &read(path)?)?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for (index, source) in map.external_tilesets()? {
+            let xml = read(&dir.join(&source))?;
+            map.embed_tileset(index, &xml)?;
+        }
+        Ok(map)
+    }
+
+    /// Read a map saved by Tiled (".tmx") or exported in JSON, depending on the extension
+    /// of `name`
+    pub fn parse(name: &str, source: &str) -> Result<TiledMap, MapError> {
+        match Path::new(name).extension().and_then(|ext| ext.to_str()) {
+            Some("tmx") => TiledMap::from_tmx(source),
+            _ => TiledMap::from_json(source),
+        }
+    }
+
+    /// Index and file of the external tilesets, the file is relative to the map file
+    ///
+    /// They must be embedded with [`TiledMap::embed_tileset`] before the map is used.
+    pub fn external_tilesets(&self) -> Result<Vec<(usize, String)>, MapError> {
+        let mut external = Vec::new();
+        for (index, tileset) in self.tilesets.iter().enumerate() {
+            let Some(source) = &tileset.source else {
+                continue;
+            };
+            // NOTE the external tilesets exported in JSON are not supported
+            if !source.ends_with(".tsx") {
+                return Err(MapError::Parse(format!(
+                    "tileset {} is not a TSX file",
+                    source
+                )));
+            }
+            external.push((index, source.clone()));
+        }
+        Ok(external)
+    }
+
+    /// Replace the external tileset at `index` by the content of its TSX file
+    ///
+    /// Its image is made relative to the map file.
+    pub fn embed_tileset(&mut self, index: usize, xml: &str) -> Result<(), MapError> {
+        let tileset = &mut self.tilesets[index];
+        let source = tileset.source.clone().unwrap_or_default();
+        let mut external = Tileset::from_tsx(xml, tileset.firstgid)?;
+        // The image is relative to the tileset file, make it relative to the map file
+        let tileset_dir = Path::new(&source).parent().unwrap_or(Path::new(""));
+        external.image = external
+            .image
+            .map(|image| tileset_dir.join(image).to_string_lossy().into_owned());
+        external.source = Some(source);
+        *tileset = external;
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<TiledMap, MapError> {
+        let mut map: TiledMap =
+            serde_json::from_str(json).map_err(|error| MapError::Parse(error.to_string()))?;
+        map.layers = flatten_groups(map.layers);
+        Ok(map)
+    }
+
+    /// Read a map saved in the format of Tiled (TMX)
+    ///
+    /// The tile layers must use the CSV format, the default one of Tiled. The external
+    /// tilesets are not read, see [`Tileset::source`].
+    pub fn from_tmx(xml: &str) -> Result<TiledMap, MapError> {
+        let document = Document::parse(xml).map_err(|error| MapError::Parse(error.to_string()))?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(MapError::Parse("the root element is not <map>".to_string()));
+        }
+        if attribute_or::<u8>(map, "infinite", 0)? != 0 {
+            return Err(MapError::Parse(
+                "infinite maps are not supported".to_string(),
+            ));
+        }
+
+        let tilesets = map
+            .children()
+            .filter(|child| child.has_tag_name("tileset"))
+            .map(read_tileset)
+            .collect::<Result<_, MapError>>()?;
+
+        Ok(TiledMap {
+            width: attribute(map, "width")?,
+            height: attribute(map, "height")?,
+            tilewidth: attribute(map, "tilewidth")?,
+            tileheight: attribute(map, "tileheight")?,
+            layers: flatten_groups(read_layers(map)?),
+            tilesets,
+        })
+    }
+
+    /// Layers made of tiles, from the bottom one to the top one
+    pub fn tile_layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter().filter(|layer| layer.kind == "tilelayer")
+    }
+
+    /// Layers made of objects, from the bottom one to the top one
+    pub fn object_layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers
+            .iter()
+            .filter(|layer| layer.kind == "objectgroup")
+    }
+
+    /// Tileset of the global tile id and the id of the tile inside it
+    ///
+    /// Return `None` for empty cells.
+    pub fn tile(&self, gid: u32) -> Option<(&Tileset, u32)> {
+        let gid = gid & !GID_FLAGS;
+        if gid == 0 {
+            return None;
+        }
+
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.firstgid <= gid)
+            .max_by_key(|tileset| tileset.firstgid)
+            .map(|tileset| (tileset, gid - tileset.firstgid))
+    }
+
+    /// Index of the tileset of the global tile id in `tilesets`
+    pub fn tileset_index(&self, gid: u32) -> Option<usize> {
+        let (tileset, _) = self.tile(gid)?;
+        self.tilesets
+            .iter()
+            .position(|other| other.firstgid == tileset.firstgid)
+    }
+}
+
+/// Replace the groups by their layers, in the order they are drawn
+///
+/// The offset, visibility and opacity of a group apply to all of its layers.
+fn flatten_groups(layers: Vec<Layer>) -> Vec<Layer> {
+    let mut flat = Vec::new();
+    for mut layer in layers {
+        if layer.kind != "group" {
+            flat.push(layer);
+            continue;
+        }
+        for mut child in flatten_groups(std::mem::take(&mut layer.layers)) {
+            child.offsetx += layer.offsetx;
+            child.offsety += layer.offsety;
+            child.visible &= layer.visible;
+            child.opacity *= layer.opacity;
+            flat.push(child);
+        }
+    }
+    flat
+}
+
+/// If the tile of the global id is flipped horizontally and vertically
+pub fn flips(gid: u32) -> (bool, bool) {
+    (
+        gid & FLIPPED_HORIZONTALLY != 0,
+        gid & FLIPPED_VERTICALLY != 0,
+    )
+}
+
+impl Tileset {
+    /// Read an external tileset (TSX), `firstgid` is the one given by the map using it
+    pub fn from_tsx(xml: &str, firstgid: u32) -> Result<Tileset, MapError> {
+        let document = Document::parse(xml).map_err(|error| MapError::Parse(error.to_string()))?;
+        let node = document.root_element();
+        if !node.has_tag_name("tileset") {
+            return Err(MapError::Parse(
+                "the root element is not <tileset>".to_string(),
+            ));
+        }
+
+        let mut tileset = read_tileset(node)?;
+        tileset.firstgid = firstgid;
+        Ok(tileset)
+    }
+
+    /// Custom properties of the tile, empty if it has none
+    pub fn properties(&self, id: u32) -> &[Property] {
+        self.tiles
+            .iter()
+            .find(|tile| tile.id == id)
+            .map_or(&[], |tile| &tile.properties)
+    }
+
+    /// Top left corner of the tile in the image of the tileset, in pixels
+    pub fn tile_origin(&self, id: u32) -> (u32, u32) {
+        let columns = self.columns.max(1);
+        let (column, row) = (id % columns, id / columns);
+        (
+            self.margin + column * (self.tilewidth + self.spacing),
+            self.margin + row * (self.tileheight + self.spacing),
+        )
+    }
+}
+
+// Reading of the TMX format
+// Reference: https://doc.mapeditor.org/en/stable/reference/tmx-map-format/
+
+/// Attribute of an element, an error if it is missing or invalid
+fn attribute<T: FromStr>(node: Node, name: &str) -> Result<T, MapError> {
+    let tag = node.tag_name().name();
+    let value = node
+        .attribute(name)
+        .ok_or_else(|| MapError::Parse(format!("<{}> has no \"{}\" attribute", tag, name)))?;
+    value.parse().map_err(|_| {
+        MapError::Parse(format!(
+            "attribute \"{}\" of <{}> is invalid: {}",
+            name, tag, value
+        ))
+    })
+}
+
+/// Attribute of an element, `default` if it is missing
+fn attribute_or<T: FromStr>(node: Node, name: &str, default: T) -> Result<T, MapError> {
+    match node.attribute(name) {
+        Some(_) => attribute(node, name),
+        None => Ok(default),
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+fn read_tileset(node: Node) -> Result<Tileset, MapError> {
+    let tileoffset = match child(node, "tileoffset") {
+        Some(offset) => TileOffset {
+            x: attribute_or(offset, "x", 0)?,
+            y: attribute_or(offset, "y", 0)?,
+        },
+        None => TileOffset::default(),
+    };
+    let tiles = node
+        .children()
+        .filter(|child| child.has_tag_name("tile"))
+        .map(|tile| {
+            Ok(Tile {
+                id: attribute(tile, "id")?,
+                properties: read_properties(tile)?,
+            })
+        })
+        .collect::<Result<_, MapError>>()?;
+
+    Ok(Tileset {
+        firstgid: attribute_or(node, "firstgid", 0)?,
+        source: node.attribute("source").map(str::to_string),
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        tiles,
+        image: child(node, "image")
+            .and_then(|image| image.attribute("source"))
+            .map(str::to_string),
+        columns: attribute_or(node, "columns", 0)?,
+        tilewidth: attribute_or(node, "tilewidth", 0)?,
+        tileheight: attribute_or(node, "tileheight", 0)?,
+        margin: attribute_or(node, "margin", 0)?,
+        spacing: attribute_or(node, "spacing", 0)?,
+        tileoffset,
+        objectalignment: node.attribute("objectalignment").map(str::to_string),
+    })
+}
+
+/// Layers of the map or of a group, the groups keep their own layers
+fn read_layers(node: Node) -> Result<Vec<Layer>, MapError> {
+    let mut layers = Vec::new();
+    for node in node.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "layer" => layers.push(read_layer(node, "tilelayer")?),
+            "objectgroup" => layers.push(read_layer(node, "objectgroup")?),
+            // NOTE their content is not used
+            "imagelayer" => layers.push(read_layer(node, "imagelayer")?),
+            "group" => layers.push(read_layer(node, "group")?),
+            _ => {}
+        }
+    }
+    Ok(layers)
+}
+
+/// Layer of any kind, the tiles and objects are only read for the tile and object layers
+/// and the layers only for the groups
+fn read_layer(node: Node, kind: &str) -> Result<Layer, MapError> {
+    let name = node.attribute("name").unwrap_or_default().to_string();
+
+    let data = match child(node, "data").filter(|_| kind == "tilelayer") {
+        None => Vec::new(),
+        Some(data) => match (data.attribute("encoding"), data.attribute("compression")) {
+            (Some("csv"), None) => data
+                .text()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|gid| !gid.is_empty())
+                .map(|gid| {
+                    gid.parse().map_err(|_| {
+                        MapError::Parse(format!("layer \"{}\" has an invalid tile: {}", name, gid))
+                    })
+                })
+                .collect::<Result<_, MapError>>()?,
+            // Oldest format, one element per tile
+            (None, None) => data
+                .children()
+                .filter(|child| child.has_tag_name("tile"))
+                .map(|tile| attribute_or(tile, "gid", 0))
+                .collect::<Result<_, MapError>>()?,
+            (encoding, compression) => {
+                return Err(MapError::Parse(format!(
+                    "layer \"{}\" uses the {} format, only CSV is supported",
+                    name,
+                    compression.or(encoding).unwrap_or_default()
+                )))
+            }
+        },
+    };
+
+    let objects = node
+        .children()
+        .filter(|child| kind == "objectgroup" && child.has_tag_name("object"))
+        .map(read_object)
+        .collect::<Result<_, MapError>>()?;
+
+    Ok(Layer {
+        name,
+        kind: kind.to_string(),
+        data,
+        objects,
+        opacity: attribute_or(node, "opacity", 1.)?,
+        visible: attribute_or::<u8>(node, "visible", 1)? != 0,
+        offsetx: attribute_or(node, "offsetx", 0.)?,
+        offsety: attribute_or(node, "offsety", 0.)?,
+        layers: match kind {
+            "group" => read_layers(node)?,
+            _ => Vec::new(),
+        },
+    })
+}
+
+fn read_object(node: Node) -> Result<Object, MapError> {
+    Ok(Object {
+        id: attribute(node, "id")?,
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        class: node
+            .attribute("type")
+            .or(node.attribute("class"))
+            .unwrap_or_default()
+            .to_string(),
+        x: attribute_or(node, "x", 0.)?,
+        y: attribute_or(node, "y", 0.)?,
+        width: attribute_or(node, "width", 0.)?,
+        height: attribute_or(node, "height", 0.)?,
+        gid: node
+            .attribute("gid")
+            .map(|_| attribute(node, "gid"))
+            .transpose()?,
+        point: child(node, "point").is_some(),
+        visible: attribute_or::<u8>(node, "visible", 1)? != 0,
+        properties: read_properties(node)?,
+    })
+}
+
+fn read_properties(node: Node) -> Result<Vec<Property>, MapError> {
+    let Some(properties) = child(node, "properties") else {
+        return Ok(Vec::new());
+    };
+
+    properties
+        .children()
+        .filter(|child| child.has_tag_name("property"))
+        .map(|property| {
+            let name: String = attribute(property, "name")?;
+            let kind = property.attribute("type").unwrap_or("string");
+            // The multiline strings are the content of the element
+            let value = property
+                .attribute("value")
+                .or(property.text())
+                .unwrap_or_default();
+            let invalid =
+                || MapError::Parse(format!("property \"{}\" is not a valid {}", name, kind));
+
+            let value = match kind {
+                "bool" => PropertyValue::Bool(value.parse().map_err(|_| invalid())?),
+                "int" | "object" => PropertyValue::Int(value.parse().map_err(|_| invalid())?),
+                "float" => PropertyValue::Float(value.parse().map_err(|_| invalid())?),
+                // string, file, color and class
+                _ => PropertyValue::String(value.to_string()),
+            };
+            Ok(Property { name, value })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names, offsets and visibility of the layers
+    fn summary(map: &TiledMap) -> Vec<(&str, f32, f32, bool)> {
+        map.layers
+            .iter()
+            .map(|layer| {
+                (
+                    layer.name.as_str(),
+                    layer.offsetx,
+                    layer.offsety,
+                    layer.visible,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tmx_and_json_maps_have_the_same_layers() {
+        let tmx = TiledMap::load(Path::new("assets/map1.tmx")).unwrap();
+        let json = TiledMap::load(Path::new("assets/map1.json")).unwrap();
+        assert_eq!((tmx.width, tmx.height), (json.width, json.height));
+        assert_eq!(tmx.layers, json.layers);
+        assert_eq!(tmx.tile_layers().count(), 1);
+        assert_eq!(tmx.object_layers().count(), 1);
+    }
+
+    #[test]
+    fn json_groups_are_flattened() {
+        let json = r#"{
+            "width": 1, "height": 1, "tilewidth": 32, "tileheight": 32, "tilesets": [],
+            "layers": [
+                {"name": "ground", "type": "tilelayer", "data": [1]},
+                {"name": "group", "type": "group", "offsetx": 4, "visible": false, "layers": [
+                    {"name": "trees", "type": "tilelayer", "data": [2], "offsetx": 1},
+                    {"name": "inner", "type": "group", "offsety": 8, "layers": [
+                        {"name": "roofs", "type": "tilelayer", "data": [3]}
+                    ]}
+                ]},
+                {"name": "top", "type": "objectgroup"}
+            ]
+        }"#;
+        let map = TiledMap::from_json(json).unwrap();
+        assert_eq!(
+            summary(&map),
+            [
+                ("ground", 0., 0., true),
+                ("trees", 5., 0., false),
+                ("roofs", 4., 8., false),
+                ("top", 0., 0., true),
+            ]
+        );
+        assert_eq!(map.tile_layers().count(), 3);
+    }
+
+    #[test]
+    fn tmx_groups_are_flattened() {
+        let tmx = r#"<map width="1" height="1" tilewidth="32" tileheight="32">
+            <layer name="ground"><data encoding="csv">1</data></layer>
+            <group name="group" offsetx="4" visible="0">
+                <layer name="trees" offsetx="1"><data encoding="csv">2</data></layer>
+                <group name="inner" offsety="8">
+                    <layer name="roofs"><data encoding="csv">3</data></layer>
+                </group>
+            </group>
+            <objectgroup name="top"/>
+        </map>"#;
+        let map = TiledMap::from_tmx(tmx).unwrap();
+        assert_eq!(
+            summary(&map),
+            [
+                ("ground", 0., 0., true),
+                ("trees", 5., 0., false),
+                ("roofs", 4., 8., false),
+                ("top", 0., 0., true),
+            ]
+        );
+        let data: Vec<&[u32]> = map.tile_layers().map(|layer| &layer.data[..]).collect();
+        assert_eq!(data, [[1], [2], [3]]);
+    }
+}
@@ -7,9 +7,15 @@ use macroquad::ui::{
     Skin,
 };
 
-use crate::{game::keymapping::KEY_MAPPINGS, Context};
+use crate::{game::keymapping::KEY_MAPPINGS, player_color, Context};
 
-use super::world::world_to_map;
+use super::{
+    ai::Difficulty, card::is_hovered, effect::Target, life::HasLife, player::PlayerId,
+    world::world_to_map,
+};
+
+/// Time during which a refused action is shown, in seconds
+const REFUSED_ACTION_DURATION: f64 = 2.;
 
 /// set the default style here
 pub async fn default_skin() -> Skin {
@@ -50,6 +56,10 @@ pub struct Settings {
     pub display_keymapping: bool,
     pub dark_theme: bool,
     pub debug: bool,
+    /// How the computer opponent plays
+    pub ai_difficulty: Difficulty,
+    /// Two users play on this device instead of a user against the computer
+    pub hot_seat: bool,
     pub skin: HashMap<String, Skin>,
     pub position: Vec2,
     pub window_size: Vec2,
@@ -62,6 +72,8 @@ impl Settings {
             display_keymapping: None,
             dark_theme: None,
             debug: None,
+            ai_difficulty: None,
+            hot_seat: None,
             skin: None,
             position: None,
             window_size: None,
@@ -95,6 +107,8 @@ pub struct SettingsBuilder {
     display_keymapping: Option<bool>,
     dark_theme: Option<bool>,
     debug: Option<bool>,
+    ai_difficulty: Option<Difficulty>,
+    hot_seat: Option<bool>,
     skin: Option<HashMap<String, Skin>>,
     position: Option<Vec2>,
     window_size: Option<Vec2>,
@@ -122,6 +136,16 @@ impl SettingsBuilder {
         self
     }
 
+    pub fn ai_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.ai_difficulty = Some(difficulty);
+        self
+    }
+
+    pub fn hot_seat(mut self, hot_seat: bool) -> Self {
+        self.hot_seat = Some(hot_seat);
+        self
+    }
+
     pub fn skin(mut self, skin: HashMap<String, Skin>) -> Self {
         self.skin = Some(skin);
         self
@@ -149,6 +173,8 @@ impl SettingsBuilder {
             display_keymapping: self.display_keymapping.unwrap_or(false),
             dark_theme: self.dark_theme.unwrap_or(false),
             debug: self.debug.unwrap_or(false),
+            ai_difficulty: self.ai_difficulty.unwrap_or(Difficulty::Greedy),
+            hot_seat: self.hot_seat.unwrap_or(false),
             skin: self.skin.unwrap_or(HashMap::from([(
                 "Default".to_string(),
                 default_skin().await,
@@ -160,17 +186,26 @@ impl SettingsBuilder {
 }
 
 pub fn show_settings(settings: &mut Settings) {
-    let (_, skin) = settings.skin.get_key_value(&"Default".to_string()).unwrap();
+    let (_, skin) = settings.skin.get_key_value("Default").unwrap();
     root_ui().push_skin(skin);
     settings.refresh_position();
 
     widgets::Window::new(hash!(), settings.position, settings.window_size)
         .label("Settings")
         .titlebar(true)
-        .ui(&mut *root_ui(), |ui| {
+        .ui(&mut root_ui(), |ui| {
             ui.checkbox(hash!(), "Dark theme", &mut settings.dark_theme);
             ui.checkbox(hash!(), "Debug mode", &mut settings.debug);
 
+            let difficulties = Difficulty::ALL.map(|difficulty| difficulty.to_string());
+            let names: Vec<&str> = difficulties.iter().map(String::as_str).collect();
+            let mut index = Difficulty::ALL
+                .iter()
+                .position(|difficulty| *difficulty == settings.ai_difficulty)
+                .unwrap_or(0);
+            ui.combo_box(hash!(), "AI difficulty", &names, &mut index);
+            settings.ai_difficulty = Difficulty::ALL[index];
+
             // Exit button
             if ui.button(
                 vec2(settings.window_size.x - 60., settings.window_size.y - 60.),
@@ -182,6 +217,340 @@ pub fn show_settings(settings: &mut Settings) {
         });
 }
 
+/// Shows the informations of the match next to the game title (action points, turn, etc.)
+pub fn show_hud(ctx: &Context, game_name: &str, text_color: Color) {
+    draw_text(game_name, 10.0, 20.0, 30.0, text_color);
+    let mut x = 10.0 + measure_text(game_name, None, 30, 1.0).width + 20.0;
+
+    // Action points
+    let player = ctx.game.active_player();
+    draw_poly(x + 8., 14., 8, 10., 0., BLUE);
+    x += 22.;
+    let action_points = format!("AP: {}/{}", player.get_ap_current(), player.get_ap_max());
+    draw_text(&action_points, x, 20.0, 20.0, text_color);
+    x += measure_text(&action_points, None, 20, 1.0).width + 20.0;
+
+    // Turn
+    let turn = ctx.game.turn;
+    draw_text(
+        &format!(
+            "Turn {} - {} - {} phase - Deck: {} Discard: {}",
+            turn.number,
+            player.get_name(),
+            turn.phase,
+            player.deck.len(),
+            player.deck.discard_pile.len(),
+        ),
+        x,
+        20.0,
+        20.0,
+        text_color,
+    );
+
+    // Selected creature
+    if let Some(creature) = ctx.selected_creature.and_then(|id| ctx.game.creature(id)) {
+        let selected = format!(
+            "{} - HP: {}/{} - Attack: {}{} - Movement: {}/{}",
+            creature.card.basic_info.name,
+            creature.get_life_current(),
+            creature.get_life_max(),
+            creature.card.attack,
+            if creature.can_attack() {
+                ""
+            } else {
+                " (exhausted)"
+            },
+            creature.movement_left,
+            creature.card.movement,
+        );
+        draw_text(&selected, 10.0, 45.0, 20.0, text_color);
+    }
+}
+
+/// Area of the screen where the panel of a player is shown
+pub fn player_panel(player: PlayerId) -> Rect {
+    Rect::new(screen_width() - 210., 110. + 30. * player as f32, 200., 26.)
+}
+
+/// Shows the health of each player. The players that can be targeted by the selected
+/// card or attacked by the selected creature are highlighted.
+pub fn show_player_panels(ctx: &Context, valid_targets: &[Target], text_color: Color) {
+    for (id, player) in ctx.game.players.iter().enumerate() {
+        let Rect { x, y, w, h } = player_panel(id);
+        draw_rectangle(x, y, w, h, player_color(id));
+
+        let outline = if valid_targets.contains(&Target::Player(id)) {
+            ORANGE
+        } else if id == ctx.game.turn.active_player {
+            LIME
+        } else {
+            DARKGRAY
+        };
+        draw_rectangle_lines(x, y, w, h, 3., outline);
+
+        draw_text(
+            &format!(
+                "{} - HP: {}/{}",
+                player.get_name(),
+                player.get_life_current(),
+                player.get_life_max()
+            ),
+            x + 8.,
+            y + h * 0.7,
+            18.,
+            text_color,
+        );
+    }
+}
+
+/// Draw a button with its label and return if it was clicked
+fn menu_button(button: Rect, label: &str) -> bool {
+    let hovered = is_hovered(button);
+    draw_rectangle(button.x, button.y, button.w, button.h, LIGHTGRAY);
+    draw_rectangle_lines(
+        button.x,
+        button.y,
+        button.w,
+        button.h,
+        3.,
+        if hovered { LIME } else { DARKGRAY },
+    );
+    draw_text(label, button.x + 10., button.y + 22., 20., BLACK);
+    hovered && is_mouse_button_pressed(MouseButton::Left)
+}
+
+/// Shows the maps that can be played and return the one clicked
+///
+/// The opponent, the computer or a second user, can be switched on this screen too.
+///
+/// * `error` : why the last chosen map could not be loaded
+pub fn show_map_selection(
+    maps: &[String],
+    error: Option<&str>,
+    settings: &mut Settings,
+    text_color: Color,
+) -> Option<String> {
+    let x = screen_width() / 2. - 150.;
+    let opponent = if settings.hot_seat {
+        "Opponent: second player (hot-seat)"
+    } else {
+        "Opponent: computer"
+    };
+    if menu_button(Rect::new(x, 30., 300., 32.), opponent) {
+        settings.hot_seat = !settings.hot_seat;
+    }
+
+    draw_text("Choose a map", x, 100., 30., text_color);
+    if maps.is_empty() {
+        draw_text("No map found in the assets folder", x, 140., 20., RED);
+    }
+
+    let mut chosen = None;
+    for (i, map) in maps.iter().enumerate() {
+        if menu_button(Rect::new(x, 120. + 40. * i as f32, 300., 32.), map) {
+            chosen = Some(map.clone());
+        }
+    }
+
+    if let Some(error) = error {
+        let y = 120. + 40. * maps.len() as f32 + 20.;
+        draw_text(error, x, y, 20., RED);
+    }
+    chosen
+}
+
+/// Shows a message in the middle of the screen while waiting for the other side of a
+/// LAN match
+pub fn show_waiting(message: &str, text_color: Color) {
+    let width = measure_text(message, None, 30, 1.).width;
+    draw_text(
+        message,
+        (screen_width() - width) / 2.,
+        screen_height() / 2.,
+        30.,
+        text_color,
+    );
+}
+
+/// Shows the state of the LAN match: whose turn it is or why it stopped
+///
+/// Return if the user goes back to the menu after an error.
+pub fn show_network(ctx: &Context, text_color: Color) -> bool {
+    let Some(lockstep) = &ctx.lockstep else {
+        return false;
+    };
+    let Some(error) = &ctx.net_error else {
+        if !ctx.game.is_over() && ctx.game.turn.active_player != lockstep.local_player {
+            let message = "Waiting for the other player";
+            let width = measure_text(message, None, 20, 1.).width;
+            draw_text(message, (screen_width() - width) / 2., 60., 20., text_color);
+        }
+        return false;
+    };
+
+    let panel = Rect::new(screen_width() / 2. - 220., 80., 440., 130.);
+    let background = if text_color == WHITE {
+        BLACK
+    } else {
+        LIGHTGRAY
+    };
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, background);
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 3., DARKGRAY);
+    draw_text(
+        "The LAN match stopped",
+        panel.x + 20.,
+        panel.y + 35.,
+        30.,
+        RED,
+    );
+    draw_text(
+        &error.to_string(),
+        panel.x + 20.,
+        panel.y + 65.,
+        18.,
+        text_color,
+    );
+    menu_button(
+        Rect::new(panel.center().x - 70., panel.bottom() - 45., 140., 32.),
+        "Menu",
+    )
+}
+
+/// Hides the board and the hand until the next player clicks, so that the previous one
+/// does not see their hand
+pub fn show_pass_device(ctx: &mut Context, text_color: Color) {
+    let Some(player) = ctx.pass_device else {
+        return;
+    };
+    let background = if text_color == WHITE {
+        BLACK
+    } else {
+        LIGHTGRAY
+    };
+    draw_rectangle(0., 0., screen_width(), screen_height(), background);
+
+    let name = ctx.game.players[player].get_name();
+    for (text, y, size) in [
+        (format!("Pass the device to {}", name), -20., 40.),
+        ("Click when ready".to_string(), 20., 20.),
+    ] {
+        let width = measure_text(&text, None, size as u16, 1.).width;
+        draw_text(
+            &text,
+            (screen_width() - width) / 2.,
+            screen_height() / 2. + y,
+            size,
+            player_color(player),
+        );
+    }
+
+    if is_mouse_button_pressed(MouseButton::Left) {
+        ctx.pass_device = None;
+    }
+}
+
+/// What to do once the match is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfMatch {
+    /// Play again on the same map
+    Rematch,
+    /// Go back to the map selection
+    Menu,
+}
+
+/// Shows the winner and the stats of the match once it is over, return the option
+/// clicked
+pub fn show_result(ctx: &Context, text_color: Color) -> Option<EndOfMatch> {
+    let result = ctx.game.result?;
+    let panel = Rect::new(screen_width() / 2. - 220., 80., 440., 290.);
+    let background = if text_color == WHITE {
+        BLACK
+    } else {
+        LIGHTGRAY
+    };
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, background);
+    draw_rectangle_lines(panel.x, panel.y, panel.w, panel.h, 3., DARKGRAY);
+
+    let winner = &ctx.game.players[result.winner()];
+    let title = format!("{} wins the match!", winner.get_name());
+    let width = measure_text(&title, None, 40, 1.).width;
+    draw_text(
+        &title,
+        panel.center().x - width / 2.,
+        panel.y + 40.,
+        40.,
+        text_color,
+    );
+    let reason = format!(
+        "Victory: {} - {} turns",
+        result.condition, ctx.game.turn.number
+    );
+    let width = measure_text(&reason, None, 20, 1.).width;
+    draw_text(
+        &reason,
+        panel.center().x - width / 2.,
+        panel.y + 65.,
+        20.,
+        text_color,
+    );
+
+    // One column per player
+    let stats = &ctx.game.stats;
+    let rows: [(&str, [u32; 2]); 6] = [
+        ("Cards played", stats.cards_played),
+        ("Creatures summoned", stats.creatures_summoned),
+        ("Creatures lost", stats.creatures_lost),
+        ("Damage dealt", stats.damage_dealt),
+        ("Healing done", stats.healing_done),
+        (
+            "Health left",
+            ctx.game.players.each_ref().map(|p| p.get_life_current()),
+        ),
+    ];
+    let columns = [panel.x + 240., panel.x + 340.];
+    let mut y = panel.y + 100.;
+    for (id, player) in ctx.game.players.iter().enumerate() {
+        draw_text(player.get_name(), columns[id], y, 18., player_color(id));
+    }
+    for (label, values) in rows {
+        y += 22.;
+        draw_text(label, panel.x + 20., y, 18., text_color);
+        for (id, value) in values.iter().enumerate() {
+            draw_text(&value.to_string(), columns[id], y, 18., text_color);
+        }
+    }
+
+    let mut chosen = None;
+    // NOTE the connection of a LAN match only lasts one match
+    let buttons = [("Rematch", EndOfMatch::Rematch), ("Menu", EndOfMatch::Menu)];
+    let buttons = buttons
+        .into_iter()
+        .filter(|(_, choice)| ctx.lockstep.is_none() || *choice == EndOfMatch::Menu);
+    for (i, (label, choice)) in buttons.enumerate() {
+        let button = Rect::new(
+            panel.x + 60. + 180. * i as f32,
+            panel.bottom() - 50.,
+            140.,
+            32.,
+        );
+        if menu_button(button, label) {
+            chosen = Some(choice);
+        }
+    }
+    chosen
+}
+
+/// Shows why the last action was refused, for a short time
+pub fn show_refused_action(ctx: &Context) {
+    if let Some((error, time)) = &ctx.refused_action {
+        if get_time() - time < REFUSED_ACTION_DURATION {
+            let message = format!("Cannot do that: {}", error);
+            let width = measure_text(&message, None, 20, 1.).width;
+            draw_text(&message, (screen_width() - width) / 2., 60., 20., RED);
+        }
+    }
+}
+
 /// Shows debuging info such as camera position, current screen size, cursor position, etc.
 pub fn show_debug_info(ctx: &Context, _settings: &Settings, text_color: Color) {
     // Current screen size
@@ -286,17 +655,14 @@ pub async fn keymappings_skin() -> Skin {
 
 pub fn show_keymapping(settings: &mut Settings) {
     settings.refresh_position();
-    let (_, skin) = settings
-        .skin
-        .get_key_value(&"Keymapping".to_string())
-        .unwrap();
+    let (_, skin) = settings.skin.get_key_value("Keymapping").unwrap();
 
     let mut close_clicked = false;
 
     widgets::Window::new(hash!(), settings.position, settings.window_size)
         .label("Keymappings")
         .titlebar(true)
-        .ui(&mut *root_ui(), |ui| {
+        .ui(&mut root_ui(), |ui| {
             ui.push_skin(skin);
             for (key, description) in KEY_MAPPINGS {
                 ui.separator();
@@ -0,0 +1,53 @@
+use macroquad::math::{IVec2, Vec2};
+
+use super::{animation::Facing, world::map_to_world};
+
+/// Time for a creature to walk from a tile to the next one, in seconds
+const STEP_DURATION: f64 = 0.2;
+
+/// Walk of a creature along a path, only used to animate it
+///
+/// The rules already moved the creature to the end of the path.
+pub struct Walk {
+    /// Map positions from the start of the walk to its end, both included
+    path: Vec<IVec2>,
+    start_time: f64,
+}
+
+impl Walk {
+    pub fn new(path: Vec<IVec2>, start_time: f64) -> Self {
+        Walk { path, start_time }
+    }
+
+    /// Time needed to walk the whole path, in seconds
+    pub fn duration(&self) -> f64 {
+        self.path.len().saturating_sub(1) as f64 * STEP_DURATION
+    }
+
+    /// World positions of the tiles the walker goes from and to at the given time,
+    /// and how far it went between them. `None` once it arrived.
+    fn step(&self, time: f64) -> Option<(Vec2, Vec2, f32)> {
+        let progress = ((time - self.start_time) / STEP_DURATION).max(0.);
+        let step = progress as usize;
+        let from = *self.path.get(step)?;
+        let to = *self.path.get(step + 1)?;
+
+        Some((
+            map_to_world(from),
+            map_to_world(to),
+            progress.fract() as f32,
+        ))
+    }
+
+    /// World position of the walker at the given time, `None` once it arrived
+    pub fn position(&self, time: f64) -> Option<Vec2> {
+        let (from, to, progress) = self.step(time)?;
+        Some(from.lerp(to, progress))
+    }
+
+    /// Side toward which the walker looks at the given time, `None` once it arrived
+    pub fn facing(&self, time: f64, current: Facing) -> Option<Facing> {
+        let (from, to, _) = self.step(time)?;
+        Some(Facing::towards(from, to, current))
+    }
+}
@@ -1,35 +1,364 @@
-use macroquad::{
-    camera::Camera2D,
-    input::mouse_position,
-    math::{ivec2, mat2, vec2, IVec2, Vec2},
+use glam::{ivec2, mat2, vec2, IVec2, Vec2};
+
+use super::{
+    player::PlayerId,
+    rules::CreatureId,
+    tiled::{MapError, Property, PropertyValue, TiledMap},
 };
 
 const TILE_SIZE: IVec2 = ivec2(32, 32);
 
+/// Gameplay properties of a tile, read from the custom properties of the tiles in Tiled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileProperties {
+    /// Creatures can stand on the tile
+    pub walkable: bool,
+    /// The tile is water, see [`World::is_water`]
+    pub water: bool,
+    /// Nothing can be seen through the tile
+    pub blocks_line_of_sight: bool,
+    /// Movement needed to walk onto the tile
+    pub movement_cost: u32,
+}
+
+impl Default for TileProperties {
+    fn default() -> Self {
+        TileProperties {
+            walkable: true,
+            water: false,
+            blocks_line_of_sight: false,
+            movement_cost: 1,
+        }
+    }
+}
+
+impl TileProperties {
+    /// Read the properties of a tile, the missing ones keep their default value
+    fn from_tiled(tile: u32, properties: &[Property]) -> Result<Self, MapError> {
+        let mut tile_properties = TileProperties::default();
+        for Property { name, value } in properties {
+            let invalid = || MapError::InvalidProperty {
+                tile,
+                name: name.clone(),
+            };
+            match (name.as_str(), value) {
+                ("walkable", PropertyValue::Bool(walkable)) => tile_properties.walkable = *walkable,
+                ("water", PropertyValue::Bool(water)) => tile_properties.water = *water,
+                ("blocks_line_of_sight", PropertyValue::Bool(blocks)) => {
+                    tile_properties.blocks_line_of_sight = *blocks
+                }
+                ("movement_cost", PropertyValue::Int(cost)) => {
+                    tile_properties.movement_cost = u32::try_from(*cost).map_err(|_| invalid())?
+                }
+                ("walkable" | "water" | "blocks_line_of_sight" | "movement_cost", _) => {
+                    return Err(invalid())
+                }
+                // Properties used by the editor or by the renderer
+                _ => {}
+            }
+        }
+        Ok(tile_properties)
+    }
+
+    /// Properties of a cell where `above` is placed on top of `self` (ex: a tree on grass)
+    fn stack(self, above: TileProperties) -> Self {
+        TileProperties {
+            walkable: self.walkable && above.walkable,
+            water: self.water || above.water,
+            blocks_line_of_sight: self.blocks_line_of_sight || above.blocks_line_of_sight,
+            movement_cost: self.movement_cost.max(above.movement_cost),
+        }
+    }
+}
+
+/// Cell of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tile {
+    /// Id of the ground tile in its tileset, `None` if there is nothing on the cell
+    pub id: Option<u32>,
+    /// Properties of every tile stacked on the cell
+    pub properties: TileProperties,
+}
+
+/// Area of the map where a player can summon their creatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeploymentZone {
+    pub player: PlayerId,
+    /// First map position of the zone
+    pub min: IVec2,
+    /// Last map position of the zone, included
+    pub max: IVec2,
+}
+
+impl DeploymentZone {
+    pub fn contains(&self, pos: IVec2) -> bool {
+        pos.cmpge(self.min).all() && pos.cmple(self.max).all()
+    }
+}
+
+/// Tile where a player starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpawnPoint {
+    pub player: PlayerId,
+    pub pos: IVec2,
+}
+
 /// Represent the game world
-///
-/// TODO add map data inside
+#[derive(Clone)]
 pub struct World {
     pub width: u32,
     pub heigth: u32,
+    /// Cells of the map, row by row
+    tiles: Vec<Tile>,
+    /// Creature standing on each cell, row by row
+    occupants: Vec<Option<CreatureId>>,
+    pub deployment_zones: Vec<DeploymentZone>,
+    pub spawn_points: Vec<SpawnPoint>,
+    /// Tiles the players fight for
+    pub objectives: Vec<IVec2>,
 }
 
 impl World {
+    /// Create a flat world where every tile can be walked on
     pub fn new(width: u32, heigth: u32) -> World {
-        World { width, heigth }
+        World::with_tiles(
+            width,
+            heigth,
+            vec![Tile::default(); (width * heigth) as usize],
+        )
+    }
+
+    fn with_tiles(width: u32, heigth: u32, tiles: Vec<Tile>) -> World {
+        World {
+            width,
+            heigth,
+            occupants: vec![None; tiles.len()],
+            tiles,
+            deployment_zones: World::default_deployment_zones(width, heigth),
+            spawn_points: Vec::new(),
+            objectives: Vec::new(),
+        }
+    }
+
+    /// Each player deploys on their half of the map, the first player on the top rows.
+    /// Used for the maps without deployment zones.
+    fn default_deployment_zones(width: u32, heigth: u32) -> Vec<DeploymentZone> {
+        let last = IVec2::new(width as i32 - 1, heigth as i32 - 1);
+        let middle = heigth as i32 / 2;
+        vec![
+            DeploymentZone {
+                player: 0,
+                min: IVec2::ZERO,
+                max: IVec2::new(last.x, middle - 1),
+            },
+            DeploymentZone {
+                player: 1,
+                min: IVec2::new(0, middle),
+                max: last,
+            },
+        ]
+    }
+
+    /// Create the world from the layers of a Tiled map
+    ///
+    /// The properties of the tiles of every layer are stacked, an empty cell cannot be walked on.
+    /// The deployment zones, spawn points and objectives are objects of the object layers, see
+    /// `World::read_objects`.
+    pub fn from_tiled(map: &TiledMap) -> Result<World, MapError> {
+        let size = (map.width * map.height) as usize;
+        let empty = Tile {
+            id: None,
+            properties: TileProperties {
+                walkable: false,
+                ..Default::default()
+            },
+        };
+        let mut tiles = vec![empty; size];
+
+        let mut nb_layers = 0;
+        for layer in map.tile_layers() {
+            nb_layers += 1;
+            if layer.data.len() != size {
+                return Err(MapError::LayerSize {
+                    layer: layer.name.clone(),
+                    expected: size,
+                    found: layer.data.len(),
+                });
+            }
+
+            for (cell, gid) in tiles.iter_mut().zip(&layer.data) {
+                let Some((tileset, id)) = map.tile(*gid) else {
+                    continue;
+                };
+                let properties = TileProperties::from_tiled(id, tileset.properties(id))?;
+                *cell = match cell.id {
+                    None => Tile {
+                        id: Some(id),
+                        properties,
+                    },
+                    Some(_) => Tile {
+                        properties: cell.properties.stack(properties),
+                        ..*cell
+                    },
+                };
+            }
+        }
+        if nb_layers == 0 {
+            return Err(MapError::NoTileLayer);
+        }
+
+        let mut world = World::with_tiles(map.width, map.height, tiles);
+        world.read_objects(map)?;
+        Ok(world)
+    }
+
+    /// Read the gameplay objects of the map, recognized by their class:
+    /// * "deployment_zone" : rectangle where a player can summon their creatures
+    /// * "spawn_point" : tile where a player starts
+    /// * "objective" : tiles the players fight for
+    ///
+    /// The deployment zones and spawn points have a `player` property, 1 for the first
+    /// player. Without any deployment zone, the map is split in two.
+    fn read_objects(&mut self, map: &TiledMap) -> Result<(), MapError> {
+        let mut deployment_zones = Vec::new();
+        for object in map.object_layers().flat_map(|layer| &layer.objects) {
+            let class = object.class.as_str();
+            // Objects used by the editor or by the renderer
+            if !matches!(class, "deployment_zone" | "spawn_point" | "objective") {
+                continue;
+            }
+
+            let invalid = |reason: &str| MapError::InvalidObject {
+                object: object.id,
+                reason: reason.to_string(),
+            };
+            let player = || match object.properties.iter().find(|p| p.name == "player") {
+                Some(Property {
+                    value: PropertyValue::Int(player),
+                    ..
+                }) if *player >= 1 => Ok(*player as PlayerId - 1),
+                _ => Err(invalid(
+                    "needs a \"player\" int property, 1 for the first player",
+                )),
+            };
+
+            // On isometric maps, a tile is `tileheight` pixels along each axis
+            let tile_size = map.tileheight as f32;
+            let min = (vec2(object.x, object.y) / tile_size).floor().as_ivec2();
+            let end = vec2(object.x + object.width, object.y + object.height) / tile_size;
+            // Last tile covered by the object, a point covers a single tile
+            let max = (end.ceil().as_ivec2() - IVec2::ONE).max(min);
+            if !self.within_map(min) || !self.within_map(max) {
+                return Err(invalid("is outside of the map"));
+            }
+
+            match class {
+                "deployment_zone" => deployment_zones.push(DeploymentZone {
+                    player: player()?,
+                    min,
+                    max,
+                }),
+                "spawn_point" => self.spawn_points.push(SpawnPoint {
+                    player: player()?,
+                    pos: min,
+                }),
+                _ => {
+                    for y in min.y..=max.y {
+                        for x in min.x..=max.x {
+                            self.objectives.push(ivec2(x, y));
+                        }
+                    }
+                }
+            }
+        }
+
+        if !deployment_zones.is_empty() {
+            self.deployment_zones = deployment_zones;
+        }
+        Ok(())
+    }
+
+    /// Return if the given map position is part of the map
+    pub fn within_map(&self, pos: IVec2) -> bool {
+        let IVec2 { x, y } = pos;
+
+        x >= 0 && x < self.width as i32 && y >= 0 && y < self.heigth as i32
+    }
+
+    /// Index of the cell in the grids of the world
+    fn index(&self, pos: IVec2) -> Option<usize> {
+        self.within_map(pos)
+            .then(|| (pos.y * self.width as i32 + pos.x) as usize)
+    }
+
+    pub fn tile_at(&self, pos: IVec2) -> Option<&Tile> {
+        self.tiles.get(self.index(pos)?)
+    }
+
+    /// Creature standing on the tile
+    pub fn occupant(&self, pos: IVec2) -> Option<CreatureId> {
+        self.occupants[self.index(pos)?]
     }
 
-    pub fn cursor_within_map(&self, camera: &Camera2D) -> bool {
-        let mouse_in_world = camera.screen_to_world(mouse_position().into());
-        let IVec2 { x, y } = world_to_map(mouse_in_world);
+    pub fn is_occupied(&self, pos: IVec2) -> bool {
+        self.occupant(pos).is_some()
+    }
+
+    /// Put the creature on the tile, replacing the previous occupant
+    pub fn occupy(&mut self, pos: IVec2, id: CreatureId) {
+        if let Some(index) = self.index(pos) {
+            self.occupants[index] = Some(id);
+        }
+    }
 
-        x >= 0 && x <= self.width as i32 && y >= 0 && y <= self.heigth as i32
+    /// Remove the creature standing on the tile
+    pub fn vacate(&mut self, pos: IVec2) {
+        if let Some(index) = self.index(pos) {
+            self.occupants[index] = None;
+        }
     }
 
-    pub fn cursor_position(&self, camera: &Camera2D) -> IVec2 {
-        let mouse_in_world = camera.screen_to_world(mouse_position().into());
-        world_to_map(mouse_in_world)
+    /// Return if the player can summon their creatures on the tile
+    pub fn in_deployment_zone(&self, player: PlayerId, pos: IVec2) -> bool {
+        self.deployment_zones
+            .iter()
+            .any(|zone| zone.player == player && zone.contains(pos))
     }
+
+    /// Return if creatures can stand on the tile
+    pub fn is_walkable(&self, pos: IVec2) -> bool {
+        self.tile_at(pos)
+            .is_some_and(|tile| tile.properties.walkable)
+    }
+
+    /// Return if the tile is water, false outside of the map
+    ///
+    /// `NOTE` no rule uses it yet, it is meant for the creatures that swim or fly.
+    pub fn is_water(&self, pos: IVec2) -> bool {
+        self.tile_at(pos).is_some_and(|tile| tile.properties.water)
+    }
+
+    /// Return if nothing can be seen through the tile, false outside of the map
+    ///
+    /// `NOTE` no rule uses it yet, the attacks and effects ignore the line of sight.
+    pub fn blocks_line_of_sight(&self, pos: IVec2) -> bool {
+        self.tile_at(pos)
+            .is_some_and(|tile| tile.properties.blocks_line_of_sight)
+    }
+
+    /// Map positions next to the given one, along the axes of the map
+    pub fn neighbors(&self, pos: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y]
+            .into_iter()
+            .map(move |dir| pos + dir)
+            .filter(|next| self.within_map(*next))
+    }
+}
+
+/// Number of steps between two map positions when moving only along the axes of the map
+pub fn distance(from: IVec2, to: IVec2) -> u32 {
+    let IVec2 { x, y } = (to - from).abs();
+    (x + y) as u32
 }
 
 // My thanks to the following PR https://github.com/not-fl3/macroquad/pull/598/commits/903333bea9747d490c360d9a1a91aa21f37ba379
@@ -41,17 +370,141 @@ pub fn world_to_map(world_pos: Vec2) -> IVec2 {
     let jhat = vec2(-0.5, 0.25) * TILE_SIZE.as_vec2();
     let inverse = mat2(ihat, jhat).inverse();
 
-    inverse.mul_vec2(world_pos).as_ivec2()
+    // Rounded down, the points just outside the top and left edges are not on the map
+    inverse.mul_vec2(world_pos).floor().as_ivec2()
 }
 
 // Transform map position to world position.
 // Reference: https://youtu.be/04oQ2jOUjkU
+// It is the top left corner of the tile's texture.
 pub fn map_to_world(map_pos: IVec2) -> Vec2 {
+    let offset = ivec2(-TILE_SIZE.x / 2, 0);
+
+    map_point_to_world(map_pos.as_vec2()) + offset.as_vec2()
+}
+
+/// World position of a point of the map, in tiles. The top corner of a tile is at its
+/// map position.
+pub fn map_point_to_world(map_pos: Vec2) -> Vec2 {
     let ihat = vec2(0.5, 0.25) * TILE_SIZE.as_vec2();
     let jhat = vec2(-0.5, 0.25) * TILE_SIZE.as_vec2();
 
-    let transform = mat2(ihat, jhat);
-    let offset = ivec2(-TILE_SIZE.x / 2, 0);
+    mat2(ihat, jhat).mul_vec2(map_pos)
+}
+
+/// Isometric depth of a world position: the map x + y of the tile drawn there, greater
+/// when closer to the viewer
+pub fn world_depth(world_pos: Vec2) -> f32 {
+    world_pos.y / (0.25 * TILE_SIZE.y as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(name: &str, value: PropertyValue) -> Property {
+        Property {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    /// 3x2 world whose middle tile of the first row is a pond and the last one a wall
+    fn world() -> World {
+        let pond = TileProperties::from_tiled(
+            1,
+            &[
+                property("water", PropertyValue::Bool(true)),
+                property("movement_cost", PropertyValue::Int(3)),
+            ],
+        )
+        .unwrap();
+        let wall = TileProperties::from_tiled(
+            2,
+            &[
+                property("walkable", PropertyValue::Bool(false)),
+                property("blocks_line_of_sight", PropertyValue::Bool(true)),
+            ],
+        )
+        .unwrap();
+        let mut tiles = vec![Tile::default(); 6];
+        tiles[1].properties = pond;
+        tiles[2].properties = wall;
+        World::with_tiles(3, 2, tiles)
+    }
+
+    #[test]
+    fn tile_properties_are_read_and_stacked() {
+        let world = world();
+        let pond = world.tile_at(ivec2(1, 0)).unwrap().properties;
+        assert!(pond.water && pond.walkable);
+        assert_eq!(pond.movement_cost, 3);
+
+        let wall = world.tile_at(ivec2(2, 0)).unwrap().properties;
+        let stacked = pond.stack(wall);
+        assert!(stacked.water && !stacked.walkable && stacked.blocks_line_of_sight);
+        assert_eq!(stacked.movement_cost, 3);
 
-    transform.mul_vec2(map_pos.as_vec2()) + offset.as_vec2()
+        let wrong_type = property("walkable", PropertyValue::Int(1));
+        assert_eq!(
+            TileProperties::from_tiled(7, &[wrong_type]),
+            Err(MapError::InvalidProperty {
+                tile: 7,
+                name: "walkable".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn tile_queries() {
+        let world = world();
+        assert_eq!(world.tile_at(ivec2(0, 1)), Some(&Tile::default()));
+        assert_eq!(world.tile_at(ivec2(3, 0)), None);
+        assert_eq!(world.tile_at(ivec2(0, -1)), None);
+
+        assert!(world.is_walkable(ivec2(1, 0)));
+        assert!(!world.is_walkable(ivec2(2, 0)));
+        assert!(!world.is_walkable(ivec2(-1, 0)));
+        assert!(world.is_water(ivec2(1, 0)));
+        assert!(!world.is_water(ivec2(0, 0)));
+        assert!(world.blocks_line_of_sight(ivec2(2, 0)));
+        assert!(!world.blocks_line_of_sight(ivec2(1, 0)));
+    }
+
+    #[test]
+    fn neighbors_stay_on_the_map() {
+        let world = world();
+        let corner: Vec<IVec2> = world.neighbors(ivec2(0, 0)).collect();
+        assert_eq!(corner, [ivec2(1, 0), ivec2(0, 1)]);
+        let edge: Vec<IVec2> = world.neighbors(ivec2(1, 1)).collect();
+        assert_eq!(edge, [ivec2(2, 1), ivec2(0, 1), ivec2(1, 0)]);
+    }
+
+    #[test]
+    fn points_outside_the_top_and_left_edges_are_out_of_the_map() {
+        let world = World::new(4, 4);
+        for (point, tile) in [
+            (vec2(-0.5, 3.5), ivec2(-1, 3)),
+            (vec2(2.5, -0.5), ivec2(2, -1)),
+            (vec2(-0.5, -0.5), ivec2(-1, -1)),
+        ] {
+            let pos = world_to_map(map_point_to_world(point));
+            assert_eq!(pos, tile);
+            assert!(!world.within_map(pos));
+        }
+    }
+
+    #[test]
+    fn points_inside_the_edges_are_on_the_map() {
+        let world = World::new(4, 4);
+        for (point, tile) in [
+            (vec2(0.5, 3.5), ivec2(0, 3)),
+            (vec2(3.5, 0.5), ivec2(3, 0)),
+            (vec2(3.9, 3.9), ivec2(3, 3)),
+        ] {
+            let pos = world_to_map(map_point_to_world(point));
+            assert_eq!(pos, tile);
+            assert!(world.within_map(pos));
+        }
+    }
 }
@@ -0,0 +1,20 @@
+//! Game logic shared by the game client and the tools.
+//!
+//! Nothing declared here may depend on macroquad, so that the rules can be
+//! compiled and ran headlessly (unit tests, simulations, servers, AI, etc.).
+//! The rendering side of `game` lives in the binary and only reads this state.
+
+pub mod game {
+    pub mod ai;
+    pub mod card_data;
+    pub mod deck;
+    pub mod effect;
+    pub mod life;
+    pub mod mcts;
+    pub mod net;
+    pub mod pathfinding;
+    pub mod player;
+    pub mod rules;
+    pub mod tiled;
+    pub mod world;
+}
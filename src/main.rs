@@ -1,23 +1,52 @@
 mod game;
 use std::collections::HashMap;
-
-use game::card::{Card, CardBasicInfo, EffectCard};
-use game::effect::{Effect, EffectType};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use game::ai::Ai;
+use game::animation::{Animator, Clip, Facing};
+use game::card::is_hovered;
+use game::card::Sprites;
+use game::card_data::{self, load_cards};
+use game::deck::Deck;
+use game::effect::Target;
 use game::hand::Hand;
 use game::keymapping::apply_input;
+use game::map::{list_maps, load_map};
+use game::net::{
+    check_sync, state_hash, Host, Lockstep, Message, NetError, Peer, Setup, DEFAULT_PORT,
+    HOST_PLAYER,
+};
+use game::player::{Player, PlayerId};
+use game::render::RenderQueue;
+use game::rules::{Action, Card, CreatureId, Event, Match, Phase, RuleError, VictoryCondition};
 
-use game::card::CreatureCard;
 use game::ui::*;
-use game::world::{map_to_world, world_to_map, World};
+use game::walk::Walk;
+use game::world::{map_to_world, world_to_map};
 use macroquad::{prelude::*, ui::root_ui};
-use macroquad_tiled::{self as tiled};
 
 // NOTE susceptible to change
 // const TILE_SIZE: IVec2 = ivec2(32, 32);
-const MAP_SIZE: IVec2 = ivec2(16, 16);
-const NB_TILE_TYPE: usize = 115;
 
 const CAM_SPEED: f32 = 10.;
+/// Every card of this folder is part of the starter deck
+const CARDS_DIR: &str = "assets/cards";
+/// Folder of the maps, the assets folder
+const MAPS_DIR: &str = "assets";
+/// Player controlled by the computer
+const AI_PLAYER: PlayerId = 1;
+/// Time left between two actions of the AI so that the user can follow them, in seconds
+const AI_ACTION_DELAY: f64 = 0.6;
+
+/// LAN match asked on the command line, it lasts one match
+enum Lan {
+    /// `--host`: a guest is awaited once the map is chosen
+    Host(Host),
+    /// `--join <address>`: the match is played on the map chosen by the host
+    Join(Peer),
+}
 
 // enum Entity {
 //     Creature(CreatureCard),
@@ -25,203 +54,808 @@ const CAM_SPEED: f32 = 10.;
 // }
 
 struct Context {
-    /// map_dimensions -> (width: u32, width: u32)
-    pub world: World,
+    /// State of the match, the rest of the context is only used to display it
+    pub game: Match,
     pub camera: Camera2D,
     pub last_mouse_position: Vec2,
     pub hand: Hand,
-    /// Vec of creatures and their positions
-    pub creatures: Vec<(CreatureCard, IVec2)>,
+    /// Textures of the cards and creatures
+    pub sprites: Sprites,
+    /// Last action refused by the rules and the time at which it happened
+    pub refused_action: Option<(RuleError, f64)>,
+    /// Creature of the active player selected on the board, ready to be moved
+    pub selected_creature: Option<CreatureId>,
+    /// Creatures currently walking on the board
+    pub walks: HashMap<CreatureId, Walk>,
+    /// Clip played by each creature of the board
+    pub animators: HashMap<CreatureId, Animator>,
+    /// Dead creatures playing their death clip, with their owner and position
+    pub dying: Vec<(Animator, PlayerId, IVec2)>,
+    /// Computer opponent, `None` when both players are users
+    pub ai: Option<Ai>,
+    /// Time after which the AI can do its next action
+    pub ai_ready_at: f64,
+    /// Search of the AI running on its own thread, it sends back the AI with its action
+    /// so that the next search goes on with the same random numbers
+    pub ai_search: Option<Receiver<(Ai, Option<Action>)>>,
+    /// Player to hand the device to in hot-seat matches, the board and the hands are
+    /// hidden until they are ready
+    pub pass_device: Option<PlayerId>,
+    /// Camera target and zoom of each player, restored at the start of their turns in
+    /// hot-seat matches
+    pub views: [(Vec2, Vec2); 2],
+    /// Connection to the other user in LAN matches
+    pub lockstep: Option<Lockstep>,
+    /// Why the LAN match stopped, the match cannot go on
+    pub net_error: Option<NetError>,
 }
 
-fn format_digit(mut digit: usize, nb_displayed_digit: usize) -> String {
-    if digit == 0 {
-        return "000".to_string();
+impl Context {
+    /// Map position under the cursor
+    pub fn cursor_tile(&self) -> IVec2 {
+        let mouse_in_world = self.camera.screen_to_world(mouse_position().into());
+        world_to_map(mouse_in_world)
     }
 
-    let default_digit = digit;
-    let mut count = 0;
-    while digit != 0 {
-        digit = digit / 10;
-        count += 1;
+    /// Player whose hand is shown and who plays with the mouse and the keyboard
+    pub fn user_player(&self) -> PlayerId {
+        match (&self.ai, &self.lockstep) {
+            (_, Some(lockstep)) => lockstep.local_player,
+            (Some(ai), None) => Match::opponent(ai.player),
+            (None, None) => self.game.turn.active_player,
+        }
     }
 
-    let mut zeros = "".to_string();
-    for _ in 0..nb_displayed_digit - count {
-        zeros.push('0');
+    /// Return if the user can act: it is not the turn of the AI or of the other side of
+    /// a LAN match, nor is the device being passed to the other user
+    pub fn is_user_turn(&self) -> bool {
+        self.user_player() == self.game.turn.active_player
+            && self.pass_device.is_none()
+            && self.net_error.is_none()
     }
 
-    return format!("{}{}", zeros, default_digit).to_string();
-}
-#[macroquad::main("CardGame")]
-async fn main() {
-    let game_name = "Funny Game";
-    let mut text_color: Color;
+    /// Return if both players use this device
+    pub fn is_hot_seat(&self) -> bool {
+        self.ai.is_none() && self.lockstep.is_none()
+    }
 
-    let mut settings = Settings::builder()
-        .skin(HashMap::from([
-            ("Default".to_string(), default_skin().await),
-            ("Keymapping".to_string(), keymappings_skin().await),
-        ]))
-        .build()
-        .await;
+    /// Let the AI do its next action during its turn, once the creatures are done walking
+    ///
+    /// The AI thinks on its own thread, up to the time budget of the MCTS, so that the
+    /// frames keep being drawn. Its action is applied on the frame it arrives.
+    pub fn play_ai(&mut self) {
+        let time = get_time();
+        if let Some(search) = &self.ai_search {
+            match search.try_recv() {
+                Ok((ai, action)) => {
+                    self.ai_search = None;
+                    self.ai = Some(ai);
+                    if let Some(action) = action {
+                        self.apply_action(action);
+                        self.ai_ready_at = time + AI_ACTION_DELAY;
+                    }
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.ai_search = None,
+            }
+            return;
+        }
+        if self.is_user_turn() || !self.walks.is_empty() || time < self.ai_ready_at {
+            return;
+        }
+        let Some(mut ai) = self.ai.clone() else {
+            return;
+        };
+        let game = self.game.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let action = ai.choose(&game);
+            // The match may have been left during the search
+            let _ = sender.send((ai, action));
+        });
+        self.ai_search = Some(receiver);
+    }
 
-    set_pc_assets_folder("assets");
+    /// Apply the next action of the other side of a LAN match once it arrived and the
+    /// creatures are done walking
+    pub fn play_remote(&mut self) {
+        if self.net_error.is_some() || self.game.is_over() || !self.walks.is_empty() {
+            return;
+        }
+        let previous_player = self.game.turn.active_player;
+        let Some(lockstep) = &mut self.lockstep else {
+            return;
+        };
+        match lockstep.poll(&mut self.game) {
+            Ok(Some(events)) => self.applied(events, false, previous_player),
+            Ok(None) => {}
+            Err(error) => self.net_error = Some(error),
+        }
+    }
+
+    /// Apply an action of the user on the match
+    ///
+    /// Return if the action was accepted by the rules.
+    pub fn apply_action(&mut self, action: Action) -> bool {
+        // A creature stays selected while it walks so that it can keep moving
+        let keep_creature = matches!(action, Action::MoveCreature { .. });
+        let previous_player = self.game.turn.active_player;
+
+        match self.game.apply(action.clone()) {
+            Ok(events) => {
+                if let Some(lockstep) = &mut self.lockstep {
+                    if let Err(error) = lockstep.send(action, &self.game) {
+                        self.net_error = Some(error);
+                    }
+                }
+                self.applied(events, keep_creature, previous_player);
+                true
+            }
+            Err(error) => {
+                self.refused_action = Some((error, get_time()));
+                false
+            }
+        }
+    }
 
-    let tileset = load_texture("spritesheet.png").await.unwrap();
-    tileset.set_filter(FilterMode::Nearest);
+    /// Update the display after an action was applied on the match
+    fn applied(&mut self, events: Vec<Event>, keep_creature: bool, previous_player: PlayerId) {
+        // The cards in hand may have changed
+        self.hand.unselect();
+        if !keep_creature {
+            self.selected_creature = None;
+        }
+        let time = get_time();
+        for event in events {
+            self.animate(event, time);
+        }
+        let next_player = self.game.turn.active_player;
+        if self.is_hot_seat() && next_player != previous_player && !self.game.is_over() {
+            self.swap_users(previous_player, next_player);
+        }
+    }
 
-    let tiled_map_json = load_string("map1.json").await.unwrap();
-    let tiled_map = tiled::load_map(&tiled_map_json, &[("spritesheet.png", tileset)], &[]).unwrap();
+    /// Hand the device over to the next user of a hot-seat match, with their own view
+    /// of the board
+    fn swap_users(&mut self, previous: PlayerId, next: PlayerId) {
+        self.views[previous] = (self.camera.target, self.camera.zoom);
+        (self.camera.target, self.camera.zoom) = self.views[next];
+        self.selected_creature = None;
+        self.pass_device = Some(next);
+    }
 
-    let mut tiles_textures: Vec<Texture2D> = Vec::with_capacity(NB_TILE_TYPE);
+    /// Start the clips of the creatures concerned by an event of the match
+    fn animate(&mut self, event: Event, time: f64) {
+        match event {
+            Event::CreatureWalked { id, path } => {
+                let walk = Walk::new(path, time);
+                let duration = walk.duration();
+                self.walks.insert(id, walk);
+                if let Some(animator) = self.animator(id) {
+                    animator.play(Clip::Walk, time, Some(duration));
+                }
+            }
+            Event::CreatureAttacked { attacker, target } => {
+                if let Target::Creature(target) = target {
+                    self.face(attacker, target);
+                }
+                self.play_once(attacker, Clip::Attack, time);
+            }
+            Event::CreatureRetaliated { defender, attacker } => {
+                self.face(defender, attacker);
+                self.play_once(defender, Clip::Attack, time);
+            }
+            Event::CreatureDamaged { id, .. } => self.play_once(id, Clip::Hurt, time),
+            Event::CreatureDied { id, owner, pos } => {
+                if let Some(mut animator) = self.animators.remove(&id) {
+                    let duration = self
+                        .sprites
+                        .creature(&animator.img_path)
+                        .map_or(0., |sprite| sprite.animation(Clip::Die).duration());
+                    animator.play(Clip::Die, time, Some(duration));
+                    self.dying.push((animator, owner, pos));
+                }
+            }
+            _ => {}
+        }
+    }
 
-    // CAN BE OPTIMIZED
-    for i in 0..NB_TILE_TYPE {
-        let texture_name = format!("isometric_tileset/tile_{}.png", format_digit(i, 3));
-        tiles_textures.push(load_texture(&texture_name).await.unwrap());
+    /// Animator of a creature of the board, created on first use
+    fn animator(&mut self, id: CreatureId) -> Option<&mut Animator> {
+        let creature = self.game.creature(id)?;
+        Some(
+            self.animators
+                .entry(id)
+                .or_insert_with(|| Animator::new(&creature.card.img_path, get_time())),
+        )
     }
 
-    let layer = &tiled_map.layers["main layer"];
-    // let layer = tiled_map.raw_tiled_map;
+    /// Play a clip of a creature once, then it goes back to idle
+    fn play_once(&mut self, id: CreatureId, clip: Clip, time: f64) {
+        let Some(creature) = self.game.creature(id) else {
+            return;
+        };
+        let duration = self
+            .sprites
+            .creature(&creature.card.img_path)
+            .map_or(0., |sprite| sprite.animation(clip).duration());
+        if let Some(animator) = self.animator(id) {
+            animator.play(clip, time, Some(duration));
+        }
+    }
 
-    let cam_area = vec2(32. * 24., 32. * 18.);
-    // Assumption here is the world origin is 0, 0.
-    let cam_pos = vec2(-cam_area.x / 2., -cam_area.y / 2.);
-    let camera =
-        Camera2D::from_display_rect(Rect::new(cam_pos.x, -cam_pos.y, cam_area.x, -cam_area.y));
+    /// Turn a creature toward another one
+    fn face(&mut self, id: CreatureId, other: CreatureId) {
+        let (Some(creature), Some(other)) = (self.game.creature(id), self.game.creature(other))
+        else {
+            return;
+        };
+        let (from, to) = (map_to_world(creature.pos), map_to_world(other.pos));
+        if let Some(animator) = self.animator(id) {
+            animator.facing = Facing::towards(from, to, animator.facing);
+        }
+    }
 
-    let mut ctx: Context = Context {
-        world: World::new(layer.width, layer.height),
-        camera: camera,
-        last_mouse_position: mouse_position().into(),
-        hand: Hand::new(1.4, (screen_width() * 0.2, screen_height() * 0.3)),
-        creatures: Vec::new(),
-    };
+    /// Return if what is selected, a card or a creature, can be used on the tile under the cursor
+    ///
+    /// `None` when nothing is selected or the cursor is outside of the map.
+    pub fn cursor_tile_validity(&self) -> Option<bool> {
+        let tile = self.cursor_tile();
+        if !self.game.world.within_map(tile) {
+            return None;
+        }
 
-    let creature_card: Card = game::card::Card::Creature(CreatureCard::new(
-        CardBasicInfo {
-            name:"Goblin".to_string(), 
-            description:"Vilest of creatures.\nHostile to all and detesable to it's very core.\nNo guilt must be felt when killing one.".to_string(),
-            cost:1,
-            // counter:None,
-            card_color:BEIGE,
+        if let Some(card) = self.hand.selected() {
+            let mut targets = self.hand.chosen_targets.clone();
+            targets.push(self.target_under_cursor(card)?);
+            return Some(self.game.check_targets(card, &targets).is_ok());
+        }
+
+        let creature = self.selected_creature?;
+        let action = match self.game.creature_at(tile) {
+            Some(other) => Action::Attack {
+                attacker: creature,
+                target: Target::Creature(other.id),
             },
-        4,
-        4,
-        "creatures/goblin/goblin",
-        2,
-        0.5
-    ));
-
-    let creature_card2: Card = game::card::Card::Creature(CreatureCard::new(
-        CardBasicInfo {
-            name: "Monkey Knight".to_string(),
-            description: "Likes banana".to_string(),
-            cost: 1,
-            // counter: None,
-            card_color: BEIGE,
-        },
-        4,
-        4,
-        "/creatures/monkey_knight/monkey_knight",
-        4,
-        0.25,
-    ));
-
-    let effect_card: Card = game::card::Card::Effect(EffectCard::new(
-        CardBasicInfo {
-            name: "Fire Ball".to_string(),
-            description: "One of the most simple spell, yet a spell to be feared".to_string(),
-            cost: 2,
-            // counter: Some(0),
-            card_color: RED,
-        },
-        Effect {
-            effect_type: EffectType::Damage,
-            nb: 4,
-        },
-    ));
-
-    ctx.hand.add_card(creature_card).await;
-    ctx.hand.add_card(effect_card).await;
-    ctx.hand.add_card(creature_card2).await;
-
-    loop {
-        clear_background(GRAY);
-        if settings.dark_theme {
-            clear_background(BLACK);
-            text_color = WHITE;
-        } else {
-            clear_background(LIGHTGRAY);
-            text_color = BLACK;
-        }
-
-        // User input
-        if is_key_pressed(KeyCode::Q) {
-            break;
-        }
-        apply_input(&mut ctx, &mut settings).await;
-
-        // Draw tiles in camera perspective
-        set_camera(&ctx.camera);
-        draw_rectangle_lines(cam_pos.x, -cam_pos.y, cam_area.x, -cam_area.y, 2., RED);
-        draw_text("Isometric map here", 0.0, 0.0, 30.0, text_color);
-
-        let mouse_in_world = ctx.camera.screen_to_world(mouse_position().into());
-        for y in 0..MAP_SIZE.y {
-            for x in 0..MAP_SIZE.x {
-                let texture_id = layer.data[y as usize * MAP_SIZE.y as usize + x as usize]
-                    .as_ref()
-                    .unwrap()
-                    .id as usize;
-                let texture = &tiles_textures[texture_id];
-
-                let world_pos = map_to_world(ivec2(x, y));
-
-                // When hovering tile
-                if ivec2(x, y) == world_to_map(mouse_in_world) && ctx.hand.card_is_selected() {
-                    draw_texture(&texture, world_pos.x - 0.8, world_pos.y - 0.8, GREEN);
+            None => Action::MoveCreature { creature, to: tile },
+        };
+        Some(self.game.validate(&action).is_ok())
+    }
+
+    /// Next target under the cursor for the card at the index `card` of the hand
+    ///
+    /// The cursor can either be on a player's panel, on a creature or on a tile. When
+    /// the rules accept none of them, the first one is returned so that the refusal can be shown.
+    pub fn target_under_cursor(&self, card: usize) -> Option<Target> {
+        if let Some(player) = (0..self.game.players.len()).find(|p| is_hovered(player_panel(*p))) {
+            return Some(Target::Player(player));
+        }
+
+        let tile = self.cursor_tile();
+        if !self.game.world.within_map(tile) {
+            return None;
+        }
+
+        let mut candidates = Vec::new();
+        if let Some(creature) = self.game.creature_at(tile) {
+            candidates.push(Target::Creature(creature.id));
+        }
+        candidates.push(Target::Tile(tile));
+
+        let is_valid = |target: &&Target| {
+            let mut targets = self.hand.chosen_targets.clone();
+            targets.push(**target);
+            self.game.check_targets(card, &targets).is_ok()
+        };
+        candidates
+            .iter()
+            .find(is_valid)
+            .or(candidates.first())
+            .copied()
+    }
+
+    /// Select the creature of the active player under the cursor, or use the selected
+    /// creature on what is under the cursor: attack an enemy or walk to a tile
+    pub fn click_board(&mut self) {
+        let tile = self.cursor_tile();
+        let active_player = self.game.turn.active_player;
+        let hovered_player = (0..self.game.players.len()).find(|p| is_hovered(player_panel(*p)));
+
+        match (self.game.creature_at(tile), self.selected_creature) {
+            (Some(creature), _) if creature.owner == active_player => {
+                self.selected_creature = if self.selected_creature == Some(creature.id) {
+                    None
                 } else {
-                    draw_texture(&texture, world_pos.x, world_pos.y, WHITE);
+                    Some(creature.id)
                 };
             }
+            (_, Some(attacker)) if hovered_player.is_some() => {
+                if let Some(player) = hovered_player {
+                    self.apply_action(Action::Attack {
+                        attacker,
+                        target: Target::Player(player),
+                    });
+                }
+            }
+            (Some(enemy), Some(attacker)) => {
+                let target = Target::Creature(enemy.id);
+                self.apply_action(Action::Attack { attacker, target });
+            }
+            (None, Some(creature)) => {
+                self.apply_action(Action::MoveCreature { creature, to: tile });
+            }
+            (_, None) => {}
         }
+    }
+
+    /// Add a target to the selected card, the card is played once it has all its targets
+    pub fn choose_target(&mut self, card: usize, target: Target) {
+        let mut targets = self.hand.chosen_targets.clone();
+        targets.push(target);
 
-        // Draw creatures
-        for (creature, pos) in &mut ctx.creatures {
-            let Vec2 { x, y } = map_to_world(*pos);
-            // -16 because the tile heigth are 16 and those of the creature img are 32
-            creature.draw_creature(x, y - 16., WHITE);
+        if targets.len() == self.game.active_player().hand[card].nb_targets() {
+            self.apply_action(Action::PlayCard { card, targets });
+            return;
         }
 
-        // 2D context
-        set_default_camera();
-        draw_text(game_name, 10.0, 20.0, 30.0, text_color);
+        match self.game.check_targets(card, &targets) {
+            Ok(()) => self.hand.chosen_targets = targets,
+            Err(error) => self.refused_action = Some((error, get_time())),
+        }
+    }
+}
 
-        // Hand
-        ctx.hand.display_hand(16., text_color);
+/// Shuffled deck given to each player at the start of a match, see [`card_data::starter_deck`]
+fn starter_deck(deck_id: u32, cards: &[Card], seed: u64) -> Deck {
+    let mut deck = Deck::new(deck_id, card_data::starter_deck(cards), seed);
+    deck.shuffle();
+    deck
+}
 
-        // Buttons
-        let (_, skin) = settings.skin.get_key_value(&"Default".to_string()).unwrap();
-        root_ui().push_skin(skin);
-        if root_ui().button(vec2(screen_width() - 80., 20.), "Settings  ") {
-            settings.toggle_display_settings();
-        }
+/// Color used to tint what belongs to a player
+pub fn player_color(player: PlayerId) -> Color {
+    match player {
+        0 => WHITE,
+        _ => Color::new(1., 0.6, 0.6, 1.),
+    }
+}
+
+#[macroquad::main("CardGame")]
+async fn main() {
+    let game_name = "Funny Game";
+    let mut text_color: Color;
+
+    let mut settings = Settings::builder()
+        .skin(HashMap::from([
+            ("Default".to_string(), default_skin().await),
+            ("Keymapping".to_string(), keymappings_skin().await),
+        ]))
+        .build()
+        .await;
 
-        if root_ui().button(vec2(screen_width() - 80., 40.), "Keymapping") {
-            settings.toggle_display_keymapping();
+    set_pc_assets_folder("assets");
+
+    let (cards, card_errors) = load_cards(Path::new(CARDS_DIR));
+    for error in &card_errors {
+        eprintln!("{}", error);
+    }
+    assert!(
+        !cards.is_empty(),
+        "no card could be loaded from {}",
+        CARDS_DIR
+    );
+
+    let mut sprites = Sprites::default();
+    for img_path in cards.iter().flat_map(Card::creature_img_paths) {
+        sprites.load_creature(img_path).await;
+    }
+    for missing in &sprites.missing {
+        eprintln!("{}", missing);
+    }
+
+    // The map can be given on the command line (ex: `cargo run -- map1.tmx`), otherwise
+    // it is chosen on the map selection screen. A LAN match is hosted with `--host` and
+    // joined with `--join <address of the host>`. The ways to win are chosen with
+    // `--victory` (ex: `--victory health,objectives:3`).
+    let maps = list_maps(Path::new(MAPS_DIR));
+    let mut chosen_map = None;
+    let mut lan = None;
+    let mut victory_conditions = VictoryCondition::DEFAULT.to_vec();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--victory" {
+            match VictoryCondition::parse_list(&args.next().unwrap_or_default()) {
+                Ok(conditions) => victory_conditions = conditions,
+                Err(error) => {
+                    eprintln!("--victory: {}", error);
+                    return;
+                }
+            }
+            continue;
         }
+        let connection = match arg.as_str() {
+            "--host" => Host::bind_any(DEFAULT_PORT).map(Lan::Host),
+            "--join" => match args.next() {
+                Some(addr) => Peer::connect(&addr).map(Lan::Join),
+                None => Err(NetError::Io("--join needs the address of the host".into())),
+            },
+            _ => {
+                chosen_map = Some(arg);
+                continue;
+            }
+        };
+        match connection {
+            Ok(connection) => lan = Some(connection),
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        }
+    }
+    // The guest plays on the map of the host
+    if let Some(Lan::Join(_)) = lan {
+        chosen_map = None;
+    }
 
-        root_ui().pop_skin();
+    let mut seed = miniquad::date::now() as u64;
+    'menu: loop {
+        let mut map_error = None;
+        let mut missing_assets = Vec::new();
+        // Match chosen by the host, for the guest
+        let mut setup: Option<Setup> = None;
+        let (map_name, (world, map_view)) = loop {
+            if let Some(Lan::Join(peer)) = &mut lan {
+                match peer.try_receive() {
+                    // Only the maps of the maps folder can be loaded, the name comes
+                    // from the network
+                    Ok(Some(Message::Setup(received))) if !maps.contains(&received.map) => {
+                        map_error = Some(format!(
+                            "{}: the host chose a map missing from {}",
+                            received.map, MAPS_DIR
+                        ));
+                        lan = None;
+                    }
+                    Ok(Some(Message::Setup(received))) => {
+                        chosen_map = Some(received.map.clone());
+                        setup = Some(received);
+                    }
+                    Ok(None) => {}
+                    Ok(Some(_)) => {
+                        map_error =
+                            Some(NetError::Unexpected("action before the match").to_string());
+                        lan = None;
+                    }
+                    Err(error) => {
+                        map_error = Some(error.to_string());
+                        lan = None;
+                    }
+                }
+            }
+            if let Some(name) = chosen_map.take() {
+                match load_map(&name, &mut missing_assets).await {
+                    Ok(map) => break (name, map),
+                    Err(error) => {
+                        map_error = Some(format!("{}: {}", name, error));
+                        // The guest cannot play the match of the host
+                        if setup.take().is_some() {
+                            lan = None;
+                        }
+                    }
+                }
+            }
+            if is_key_pressed(KeyCode::Q) {
+                return;
+            }
 
-        #[cfg_attr(any(), rustfmt::skip)]
-        { // Display settings related informations
-        if settings.display_settings   { show_settings(&mut settings); }
-        if settings.display_keymapping { show_keymapping(&mut settings); }
-        if settings.debug { show_debug_info(&ctx, &settings, text_color); }
+            clear_background(if settings.dark_theme {
+                BLACK
+            } else {
+                LIGHTGRAY
+            });
+            let text_color = if settings.dark_theme { WHITE } else { BLACK };
+            if let Some(Lan::Join(_)) = lan {
+                show_waiting("Waiting for the host to choose the map", text_color);
+            } else {
+                chosen_map =
+                    show_map_selection(&maps, map_error.as_deref(), &mut settings, text_color);
+            }
+            next_frame().await;
+        };
+        for missing in &missing_assets {
+            eprintln!("{}", missing);
         }
 
-        next_frame().await
+        // Other side of the LAN match and the player of this side
+        let mut peer = match lan.take() {
+            Some(Lan::Host(host)) => loop {
+                match host.accept() {
+                    Ok(Some(peer)) => break Some((peer, HOST_PLAYER)),
+                    Ok(None) => {}
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        break None;
+                    }
+                }
+                if is_key_pressed(KeyCode::Q) {
+                    return;
+                }
+                // Back to the map selection, still hosting
+                if is_key_pressed(KeyCode::Escape) {
+                    lan = Some(Lan::Host(host));
+                    continue 'menu;
+                }
+
+                let text_color = if settings.dark_theme { WHITE } else { BLACK };
+                clear_background(if settings.dark_theme {
+                    BLACK
+                } else {
+                    LIGHTGRAY
+                });
+                show_waiting(
+                    &format!(
+                        "Waiting for a player to join on port {} (Escape to go back)",
+                        DEFAULT_PORT
+                    ),
+                    text_color,
+                );
+                next_frame().await;
+            },
+            Some(Lan::Join(peer)) => Some((peer, Match::opponent(HOST_PLAYER))),
+            None => None,
+        };
+
+        // Rematches are played on the same map
+        loop {
+            let cam_area = vec2(32. * 24., 32. * 18.);
+            // Assumption here is the world origin is 0, 0.
+            let cam_pos = vec2(-cam_area.x / 2., -cam_area.y / 2.);
+            let camera = Camera2D::from_display_rect(Rect::new(
+                cam_pos.x,
+                -cam_pos.y,
+                cam_area.x,
+                -cam_area.y,
+            ));
+
+            // The guest plays with the decks and the ways to win of the host
+            if let Some(setup) = &setup {
+                seed = setup.seed;
+                victory_conditions = setup.victory_conditions.clone();
+            }
+            let players = [0, 1].map(|id| {
+                let mut player = Player::new(id, format!("Player {}", id + 1));
+                player.deck = starter_deck(id, &cards, seed + id as u64);
+                player
+            });
+            let ai = Ai::new(
+                AI_PLAYER,
+                settings.ai_difficulty,
+                seed + players.len() as u64,
+            );
+            let match_seed = seed;
+            // Each match gets its own decks and AI
+            seed += players.len() as u64 + 1;
+            let mut game = Match::new(world.clone(), players);
+            game.victory_conditions = victory_conditions.clone();
+
+            let mut net_error = None;
+            let lockstep = peer.take().map(|(mut peer, player)| {
+                let synced = match &setup {
+                    Some(setup) => check_sync(&game, setup.hash),
+                    None => peer.send(&Message::Setup(Setup {
+                        seed: match_seed,
+                        map: map_name.clone(),
+                        victory_conditions: victory_conditions.clone(),
+                        hash: state_hash(&game),
+                    })),
+                };
+                net_error = synced.err();
+                Lockstep::new(peer, player)
+            });
+
+            let mut ctx: Context = Context {
+                game,
+                views: [(camera.target, camera.zoom); 2],
+                camera,
+                last_mouse_position: mouse_position().into(),
+                hand: Hand::new(1.4, (screen_width() * 0.2, screen_height() * 0.3)),
+                sprites,
+                refused_action: None,
+                selected_creature: None,
+                walks: HashMap::new(),
+                animators: HashMap::new(),
+                dying: Vec::new(),
+                ai: (!settings.hot_seat && lockstep.is_none()).then_some(ai),
+                ai_ready_at: 0.,
+                ai_search: None,
+                pass_device: None,
+                lockstep,
+                net_error,
+            };
+
+            let end_of_match = loop {
+                clear_background(GRAY);
+                if settings.dark_theme {
+                    clear_background(BLACK);
+                    text_color = WHITE;
+                } else {
+                    clear_background(LIGHTGRAY);
+                    text_color = BLACK;
+                }
+
+                // User input
+                if is_key_pressed(KeyCode::Q) {
+                    return;
+                }
+                apply_input(&mut ctx, &mut settings);
+                if let Some(ai) = &mut ctx.ai {
+                    ai.difficulty = settings.ai_difficulty;
+                }
+                ctx.play_ai();
+                ctx.play_remote();
+
+                // Draw tiles in camera perspective
+                set_camera(&ctx.camera);
+                draw_rectangle_lines(cam_pos.x, -cam_pos.y, cam_area.x, -cam_area.y, 2., RED);
+                draw_text("Isometric map here", 0.0, 0.0, 30.0, text_color);
+
+                let valid_targets = match (ctx.hand.selected(), ctx.selected_creature) {
+                    (Some(card), _) => ctx.game.valid_targets(card, &ctx.hand.chosen_targets),
+                    (None, Some(creature)) => ctx.game.attack_targets(creature),
+                    (None, None) => Vec::new(),
+                };
+                let tile_of = |target: &Target| match target {
+                    Target::Creature(id) => ctx.game.creature(*id).map(|creature| creature.pos),
+                    Target::Tile(pos) => Some(*pos),
+                    Target::Player(_) => None,
+                };
+                // Tiles that can be targeted and those already chosen
+                let targeted_tiles: Vec<IVec2> = valid_targets.iter().filter_map(tile_of).collect();
+                let chosen_tiles: Vec<IVec2> =
+                    ctx.hand.chosen_targets.iter().filter_map(tile_of).collect();
+                // Where the selected creature can walk
+                let reachable_tiles: Vec<IVec2> = ctx
+                    .selected_creature
+                    .filter(|_| ctx.game.turn.phase == Phase::Main)
+                    .and_then(|id| ctx.game.reachable(id))
+                    .map_or_else(Vec::new, |reachable| reachable.tiles().collect());
+                let selected_tile = ctx
+                    .selected_creature
+                    .and_then(|id| ctx.game.creature(id))
+                    .map(|creature| creature.pos);
+                let cursor_tile = ctx.cursor_tile();
+                let cursor_tile_validity = ctx.cursor_tile_validity();
+
+                // Tiles and creatures are drawn back to front
+                let mut render_queue = RenderQueue::default();
+
+                map_view.queue(&mut render_queue, |cell| {
+                    // When hovering tile, shows if the selected card or creature can be used on it
+                    if let Some(valid) = cursor_tile_validity.filter(|_| cell == cursor_tile) {
+                        let color = if valid { GREEN } else { RED };
+                        (vec2(-0.8, -0.8), color)
+                    } else if chosen_tiles.contains(&cell) || selected_tile == Some(cell) {
+                        (Vec2::ZERO, LIME)
+                    } else if targeted_tiles.contains(&cell) {
+                        (Vec2::ZERO, ORANGE)
+                    } else if reachable_tiles.contains(&cell) {
+                        (Vec2::ZERO, SKYBLUE)
+                    } else if ctx.game.world.objectives.contains(&cell) {
+                        (Vec2::ZERO, GOLD)
+                    } else {
+                        (Vec2::ZERO, WHITE)
+                    }
+                });
+
+                // The dying creatures fade out during their death clip
+                let time = get_time();
+                ctx.dying.retain(|(animator, _, _)| !animator.is_over(time));
+                for (animator, owner, pos) in &ctx.dying {
+                    let mut color = player_color(*owner);
+                    color.a *= 1. - animator.progress(time) as f32;
+                    if let Some(sprite) = ctx.sprites.creature(&animator.img_path) {
+                        sprite.queue_creature(
+                            &mut render_queue,
+                            animator,
+                            time,
+                            map_to_world(*pos),
+                            color,
+                        );
+                    }
+                }
+
+                ctx.walks.retain(|_, walk| walk.position(time).is_some());
+                for creature in &ctx.game.creatures {
+                    let walk = ctx.walks.get(&creature.id);
+                    let world_pos = walk
+                        .and_then(|walk| walk.position(time))
+                        .unwrap_or_else(|| map_to_world(creature.pos));
+                    let animator = ctx
+                        .animators
+                        .entry(creature.id)
+                        .or_insert_with(|| Animator::new(&creature.card.img_path, time));
+                    animator.update(time);
+                    if let Some(facing) = walk.and_then(|walk| walk.facing(time, animator.facing)) {
+                        animator.facing = facing;
+                    }
+                    if let Some(sprite) = ctx.sprites.creature(&animator.img_path) {
+                        let color = player_color(creature.owner);
+                        sprite.queue_creature(&mut render_queue, animator, time, world_pos, color);
+                    }
+                }
+
+                render_queue.draw();
+
+                // 2D context
+                set_default_camera();
+                show_hud(&ctx, game_name, text_color);
+                show_player_panels(&ctx, &valid_targets, text_color);
+                show_refused_action(&ctx);
+
+                // Hand, hidden while the device is passed to the other user
+                if ctx.pass_device.is_none() {
+                    ctx.hand.display_hand(
+                        &ctx.game.players[ctx.user_player()],
+                        &ctx.sprites,
+                        16.,
+                        text_color,
+                    );
+                }
+                // NOTE shown before the buttons so that the click ending the turn does
+                // not also dismiss it
+                show_pass_device(&mut ctx, text_color);
+
+                // Buttons
+                let (_, skin) = settings.skin.get_key_value("Default").unwrap();
+                root_ui().push_skin(skin);
+                if root_ui().button(vec2(screen_width() - 80., 20.), "Settings  ") {
+                    settings.toggle_display_settings();
+                }
+
+                if root_ui().button(vec2(screen_width() - 80., 40.), "Keymapping") {
+                    settings.toggle_display_keymapping();
+                }
+
+                if root_ui().button(vec2(screen_width() - 80., 60.), "Next Phase")
+                    && ctx.is_user_turn()
+                {
+                    ctx.apply_action(Action::NextPhase);
+                }
+
+                if root_ui().button(vec2(screen_width() - 80., 80.), "End Turn  ")
+                    && ctx.is_user_turn()
+                {
+                    ctx.apply_action(Action::EndTurn);
+                }
+
+                root_ui().pop_skin();
+
+                #[cfg_attr(any(), rustfmt::skip)]
+                { // Display settings related informations
+                if settings.display_settings   { show_settings(&mut settings); }
+                if settings.display_keymapping { show_keymapping(&mut settings); }
+                if settings.debug { show_debug_info(&ctx, &settings, text_color); }
+                }
+
+                if show_network(&ctx, text_color) {
+                    break EndOfMatch::Menu;
+                }
+
+                // Once the last creatures are done dying
+                if ctx.game.is_over() && ctx.dying.is_empty() {
+                    if let Some(end_of_match) = show_result(&ctx, text_color) {
+                        break end_of_match;
+                    }
+                }
+
+                next_frame().await
+            };
+            sprites = ctx.sprites;
+            // The click on the result screen must not reach the next screen
+            next_frame().await;
+            match end_of_match {
+                EndOfMatch::Rematch => {}
+                EndOfMatch::Menu => continue 'menu,
+            }
+        }
     }
 }
@@ -0,0 +1,111 @@
+//! A full LAN match between two AIs over the loopback interface.
+
+use std::{path::Path, thread};
+
+use cards::game::{
+    ai::{Ai, Difficulty},
+    card_data::{load_cards, starter_deck},
+    deck::Deck,
+    net::{check_sync, state_hash, Host, Lockstep, Message, Peer, Setup, HOST_PLAYER},
+    player::{Player, PlayerId},
+    rules::Match,
+    tiled::TiledMap,
+    world::World,
+};
+
+const MAP: &str = "assets/map1.tmx";
+const SEED: u64 = 42;
+/// Matches longer than this are stopped, as in the simulations
+const MAX_TURNS: u32 = 200;
+
+/// Same match on both sides for a given seed, as built by the game
+fn new_match(seed: u64) -> Match {
+    let map = TiledMap::load(Path::new(MAP)).expect("the map loads");
+    let world = World::from_tiled(&map).expect("the map is valid");
+    let (cards, errors) = load_cards(Path::new("assets/cards"));
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let players = [0, 1].map(|id| {
+        let mut deck = Deck::new(id, starter_deck(&cards), seed + id as u64);
+        deck.shuffle();
+        let mut player = Player::new(id, format!("Player {}", id + 1));
+        player.deck = deck;
+        player
+    });
+    Match::new(world, players)
+}
+
+/// Play the match of one side until it is over, return its final state
+fn play(mut lockstep: Lockstep, mut game: Match) -> Match {
+    let player: PlayerId = lockstep.local_player;
+    let mut ai = Ai::new(player, Difficulty::Greedy, SEED + player as u64);
+    while !game.is_over() && game.turn.number <= MAX_TURNS {
+        if game.turn.active_player == player {
+            let action = ai.choose(&game).expect("the AI can always pass");
+            game.apply(action.clone())
+                .expect("the AI only does legal actions");
+            lockstep
+                .send(action, &game)
+                .expect("the other side is connected");
+        } else {
+            lockstep.wait(&mut game).expect("both sides stay in sync");
+        }
+    }
+    game
+}
+
+/// Play a full match between a host listening on `bind` and a guest connecting to the
+/// address given by `connect` for the port of the host
+fn play_over_loopback(bind: &str, connect: impl FnOnce(u16) -> String + Send + 'static) {
+    let host = Host::bind(bind).unwrap();
+    let port = host.local_addr().unwrap().port();
+
+    let guest = thread::spawn(move || {
+        let mut peer = Peer::connect(&connect(port)).unwrap();
+        let Message::Setup(setup) = peer.receive().unwrap() else {
+            panic!("the host starts with the setup");
+        };
+        assert_eq!(setup.map, MAP);
+        let mut game = new_match(setup.seed);
+        game.victory_conditions = setup.victory_conditions;
+        check_sync(&game, setup.hash).unwrap();
+        play(Lockstep::new(peer, 1 - HOST_PLAYER), game)
+    });
+
+    let mut peer = loop {
+        if let Some(peer) = host.accept().unwrap() {
+            break peer;
+        }
+        thread::yield_now();
+    };
+    let game = new_match(SEED);
+    let setup = Setup {
+        seed: SEED,
+        map: MAP.to_string(),
+        victory_conditions: game.victory_conditions.clone(),
+        hash: state_hash(&game),
+    };
+    peer.send(&Message::Setup(setup)).unwrap();
+    let host_game = play(Lockstep::new(peer, HOST_PLAYER), game);
+    let guest_game = guest.join().unwrap();
+
+    assert!(
+        host_game.is_over(),
+        "the match ends before {} turns",
+        MAX_TURNS
+    );
+    assert_eq!(host_game.result, guest_game.result);
+    assert_eq!(host_game.turn, guest_game.turn);
+    assert_eq!(state_hash(&host_game), state_hash(&guest_game));
+}
+
+#[test]
+fn loopback_match_stays_in_sync() {
+    play_over_loopback("127.0.0.1:0", |port| format!("127.0.0.1:{}", port));
+}
+
+/// The game listens on every IPv6 address, as `--host` does
+#[test]
+fn ipv6_loopback_match_stays_in_sync() {
+    play_over_loopback("[::]:0", |port| format!("[::1]:{}", port));
+}